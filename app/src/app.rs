@@ -1,60 +1,406 @@
-use scout_core::{ Shared, SearchResult };
+use gio::prelude::*;
+use std::rc::Rc;
+use std::sync::{ Arc, Mutex };
+use scout_core::{ Shared, Notifier, Notification, NotifyLevel, Updater, ResultUpdate, Tasks };
 
+use super::paths;
+use super::cli::Options;
+use super::history::{ History, Boosts };
+use super::query_log::{ self, QueryRecord, ResultKey };
+use super::dbus::{ DBusServer, ShownResult };
+use super::preferences::{ Preferences, PreferencesStore };
 use super::window::{ Window, WindowCallbacks };
+use super::plugin::{ PluginSearcher, PluginResult, LoadedPlugin, CommandPlugin, Command, TaskManager };
 
 pub struct AppCallbacks {
-	pub on_search: Box<dyn FnMut(&str) -> Vec<Box<dyn SearchResult>>>
+	pub get_plugins: Box<dyn FnMut() -> Vec<LoadedPlugin>>,
+
+	/** Reloads all plugins. This is called without the app borrowed, as plugins register stylesheets with the app. */
+	pub reload_plugins: Rc<dyn Fn()>
 }
 
 impl Default for AppCallbacks {
 	fn default() -> Self {
 		AppCallbacks {
-			on_search: Box::new(|_| vec![])
+			get_plugins: Box::new(|| vec![]),
+			reload_plugins: Rc::new(|| ())
 		}
 	}
 }
 
 pub struct App {
-	stylesheets: Vec<&'static str>,
+	/** Stylesheets added by plugins, which are copied so they outlive the plugins' libraries. */
+	stylesheets: Vec<String>,
 	window: Option<Shared<Window>>,
-	callbacks: AppCallbacks
+	searcher: Option<PluginSearcher>,
+	callbacks: AppCallbacks,
+	dbus: Shared<Option<DBusServer>>,
+	preferences: Option<PreferencesStore>,
+	history: History,
+
+	/** The latest query, and the results shown for it, which are logged when a result is activated. */
+	query: String,
+	shown: Shared<Vec<ResultKey>>,
+
+	/** The built-in plugin providing Scout's own commands, which is searched when a query starts with `:`. */
+	commands: Option<LoadedPlugin>,
+
+	/** Sends notifications from plugins to the main thread, where they are received once the window exists. */
+	notifications: Arc<Mutex<glib::Sender<Notification>>>,
+	notifications_rx: Option<glib::Receiver<Notification>>,
+
+	/** Sends updates to plugins' results to the main thread, in the same way as notifications. */
+	updates: Arc<Mutex<glib::Sender<(String, ResultUpdate)>>>,
+	updates_rx: Option<glib::Receiver<(String, ResultUpdate)>>,
+
+	/** Runs plugins' background tasks and timers. */
+	tasks: Arc<TaskManager>,
+
+	/** The plugin identifier that searches are limited to, if any. */
+	scope: Option<String>
 }
 
 impl App {
 	pub fn new() -> Shared<Self> {
+		let (notifications, notifications_rx) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+		let (updates, updates_rx) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+
 		Shared::new(App {
 			window: None,
+			searcher: None,
 			stylesheets: vec![],
-			callbacks: AppCallbacks::default()
+			callbacks: AppCallbacks::default(),
+			dbus: Shared::new(None),
+			preferences: None,
+			history: History::load(),
+			query: String::new(),
+			shown: Shared::new(vec![]),
+			commands: None,
+			notifications: Arc::new(Mutex::new(notifications)),
+			notifications_rx: Some(notifications_rx),
+			updates: Arc::new(Mutex::new(updates)),
+			updates_rx: Some(updates_rx),
+			tasks: TaskManager::new(),
+			scope: None
 		})
 	}
 
-	pub fn add_stylesheet(&mut self, stylesheet: &'static str) {
-		self.stylesheets.push(stylesheet);
+	pub fn add_stylesheet(&mut self, stylesheet: &str) {
+		self.stylesheets.push(stylesheet.to_owned());
+	}
+
+	/**
+	 * Returns a notifier for a plugin, which can be used from any thread.
+	 */
+
+	pub fn get_notifier(&self, source: &str) -> Notifier {
+		let sender = self.notifications.clone();
+		Notifier::new(source, move |notification| { sender.lock().unwrap().send(notification).ok(); })
+	}
+
+	/**
+	 * Returns an updater for a plugin's results, which can be used from any thread.
+	 */
+
+	pub fn get_updater(&self, source: &str) -> Updater {
+		let sender = self.updates.clone();
+		Updater::new(source, move |source, update| { sender.lock().unwrap().send((source.to_owned(), update)).ok(); })
+	}
+
+	/**
+	 * Returns a handle for a plugin to run tasks with, which keeps its library loaded while tasks run.
+	 */
+
+	pub fn get_tasks(&self, source: &str, library: Option<&Arc<libloading::Library>>) -> Tasks {
+		Tasks::new(self.tasks.get_runner(source, library))
+	}
+
+	/**
+	 * Waits for plugins' background tasks to finish. This is used when running without a main loop.
+	 */
+
+	pub fn flush_tasks(&self) {
+		self.tasks.flush();
 	}
 
 	pub fn bind(&mut self, callbacks: AppCallbacks) {
 		self.callbacks = callbacks;
 	}
 
-	pub fn init(&mut self, gtk: &gtk::Application, bind: &Shared<Self>) {
-		self.window = Some(Window::new(gtk, &self.stylesheets));
+	pub fn init(&mut self, gtk: &gtk::Application, bind: &Shared<Self>, config: Option<&std::path::Path>, visible: bool) {
+		let preferences = PreferencesStore::new(Preferences::new(config));
+
+		let gtk_clone = gtk.clone();
+		preferences.watch(move |path, err| {
+			let notification = gio::Notification::new("Scout couldn't read its configuration");
+			notification.set_body(Some(&format!("{}:{}:{}\n{}", path.display(), err.line(), err.column(), err)));
+			gtk_clone.send_notification(Some("config-error"), &notification);
+			log::warn!("Error reading config file {:?}, keeping the current preferences. {}", path, err);
+		});
+
+		self.preferences = Some(preferences.clone());
+		let window = Window::new(gtk, std::mem::take(&mut self.stylesheets), preferences, visible);
+		self.window = Some(window.clone());
+
+		let shown = self.shown.clone();
+		let dbus = self.dbus.clone();
+		let bind_clone = bind.clone();
+		self.searcher = Some(PluginSearcher::new(move |results| {
+			App::set_shown(&shown, &dbus, &results);
+			window.borrow_mut().set_results(results);
+		}, move |plugin, query, results| App::replace_results(&bind_clone, plugin, query, results)));
+
+		let (commands, receiver) = CommandPlugin::new();
+		self.commands = Some(LoadedPlugin::builtin("commands", Arc::new(commands)));
+
+		let bind_clone = bind.clone();
+		receiver.attach(None, move |command| { App::run_command(&bind_clone, command); glib::Continue(true) });
+
+		if let Some(receiver) = self.notifications_rx.take() {
+			let bind_clone = bind.clone();
+			let gtk_clone = gtk.clone();
+			receiver.attach(None, move |notification| {
+				App::show_notification(&bind_clone, &gtk_clone, notification);
+				glib::Continue(true)
+			});
+		}
+
+		if let Some(receiver) = self.updates_rx.take() {
+			let bind_clone = bind.clone();
+			receiver.attach(None, move |(source, update)| { App::update_results(&bind_clone, &source, update); glib::Continue(true) });
+		}
 
 		let bind_a = bind.clone();
 		let bind_b = bind.clone();
+		let bind_c = bind.clone();
+		let bind_d = bind.clone();
+		let bind_e = bind.clone();
 		self.window.as_ref().unwrap().borrow_mut().bind(WindowCallbacks {
 			on_input: Box::new(move |v| bind_a.borrow_mut().on_input(v)),
-			on_submit: Box::new(move || bind_b.borrow_mut().on_submit())
+			on_submit: Box::new(move || App::submit(&bind_b)),
+			on_activate: Box::new(move |plugin, id| bind_e.borrow_mut().on_activate(plugin, id)),
+			on_show: Box::new(move || bind_c.borrow_mut().on_visibility_changed(true)),
+			on_hide: Box::new(move || bind_d.borrow_mut().on_visibility_changed(false))
 		});
-		self.stylesheets.clear();
+	}
+
+	/**
+	 * Sets the D-Bus object to notify of visibility changes and shown results.
+	 */
+
+	pub fn set_dbus(&mut self, dbus: DBusServer) {
+		self.dbus.replace(Some(dbus));
+	}
+
+	pub fn get_window(&self) -> Option<Shared<Window>> {
+		self.window.as_ref().map(|window| window.clone())
+	}
+
+	/**
+	 * Applies command-line options, which may have been forwarded from another instance.
+	 * The app must not be borrowed when this is called, as updating the window may trigger a search.
+	 */
+
+	pub fn handle_options(app: &Shared<Self>, gtk: &gtk::Application, options: &Options) {
+		if options.quit { gtk.quit(); return; }
+
+		if !options.has_window_options() { gtk.activate(); return; }
+
+		let window = app.borrow().get_window();
+		let window = match window { Some(window) => window, None => return };
+
+		if options.hide { window.borrow_mut().hide(); return; }
+		if options.toggle && !options.show && options.query.is_none() && options.plugin.is_none() {
+			window.borrow_mut().toggle();
+			return;
+		}
+
+		app.borrow_mut().scope = options.plugin.clone();
+
+		let mut window = window.borrow_mut();
+		window.show();
+		window.set_scope(options.plugin.as_deref());
+		window.set_query(options.query.as_deref().unwrap_or(""));
+	}
+
+	/**
+	 * Unloads and reloads all plugins, clearing results and notifications that they created,
+	 * and restyling the window with the stylesheets the reloaded plugins add.
+	 */
+
+	pub fn reload_plugins(app: &Shared<Self>) {
+		let window = app.borrow().get_window();
+		if let Some(window) = &window {
+			window.borrow_mut().clear_results();
+			window.borrow().hide_notification();
+		}
+
+		let (shown, dbus) = { let app = app.borrow(); (app.shown.clone(), app.dbus.clone()) };
+		App::set_shown(&shown, &dbus, &[]);
+
+		app.borrow().tasks.cancel_all();
+		let reload = app.borrow().callbacks.reload_plugins.clone();
+		reload();
+
+		let stylesheets = std::mem::take(&mut app.borrow_mut().stylesheets);
+		if let Some(window) = window { window.borrow_mut().set_styles(stylesheets); }
+	}
+
+	/**
+	 * Applies an update to a plugin's results, if the window is visible and the plugin's results are being shown.
+	 */
+
+	fn update_results(app: &Shared<Self>, source: &str, update: ResultUpdate) {
+		let (window, query) = {
+			let app = app.borrow();
+			(app.get_window(), app.query.clone())
+		};
+
+		let visible = window.map_or(false, |window| window.borrow().is_visible());
+		let scoped_out = app.borrow().scope.as_ref().map_or(false, |scope| scope != source);
+		if !visible || scoped_out || CommandPlugin::parse_query(&query).is_some() { return; }
+
+		let plugin = (app.borrow_mut().callbacks.get_plugins)().into_iter().find(|plugin| plugin.identifier == source);
+		let plugin = match plugin { Some(plugin) => plugin, None => return };
+
+		match update {
+			ResultUpdate::Invalidate => app.borrow_mut().searcher.as_mut().unwrap().refresh(&query, plugin),
+			ResultUpdate::Replace { query: pushed, results } => {
+				let results = results.into_iter().map(|result| plugin.wrap(result)).collect();
+				App::replace_results(app, source, &pushed, results);
+			}
+		}
+	}
+
+	/**
+	 * Replaces a plugin's shown results, if they are for the current query.
+	 */
+
+	fn replace_results(app: &Shared<Self>, plugin: &str, query: &str, results: Vec<PluginResult>) {
+		let (window, current, boosts) = {
+			let app = app.borrow();
+			(app.get_window(), app.query.clone(), app.history.get_boosts())
+		};
+
+		if query != current { return; }
+		let window = match window { Some(window) => window, None => return };
+		window.borrow_mut().replace_plugin_results(plugin, query, results, &boosts);
+
+		let (shown, dbus) = { let app = app.borrow(); (app.shown.clone(), app.dbus.clone()) };
+		App::set_shown(&shown, &dbus, &window.borrow().get_shown_results());
+	}
+
+	/**
+	 * Records the results shown for the latest query, for the query log and D-Bus clients.
+	 */
+
+	fn set_shown(shown: &Shared<Vec<ResultKey>>, dbus: &Shared<Option<DBusServer>>, results: &[PluginResult]) {
+		shown.replace(results.iter().map(|res| ResultKey { plugin: res.plugin.clone(), id: res.result.get_id() }).collect());
+		if let Some(dbus) = &*dbus.borrow() {
+			dbus.set_results(results.iter().map(|res| ShownResult {
+				plugin: res.plugin.clone(), id: res.result.get_id(), title: res.result.get_title() }).collect());
+		}
+	}
+
+	/**
+	 * Shows a notification in the window if it's visible, and as a desktop notification otherwise.
+	 * Desktop notifications don't include the notification's action.
+	 */
+
+	fn show_notification(app: &Shared<Self>, gtk: &gtk::Application, notification: Notification) {
+		log::debug!("Notification from '{}': {}", notification.source, notification.message);
+
+		let window = app.borrow().get_window();
+		match window {
+			Some(window) if window.borrow().is_visible() => window.borrow().show_notification(&notification),
+			_ => {
+				let desktop = gio::Notification::new("Scout");
+				desktop.set_body(Some(&notification.message));
+				if notification.level == NotifyLevel::Error { desktop.set_priority(gio::NotificationPriority::High); }
+				gtk.send_notification(None, &desktop);
+			}
+		}
+	}
+
+	/**
+	 * Runs one of Scout's own commands.
+	 * The app must not be borrowed when this is called, as commands may update the window.
+	 */
+
+	pub fn run_command(app: &Shared<Self>, command: Command) {
+		let window = app.borrow().get_window();
+		let preferences = app.borrow().preferences.clone();
+
+		let open = |path: &std::path::Path| {
+			let uri = gio::File::new_for_path(path).get_uri();
+			if let Err(err) = gio::AppInfo::launch_default_for_uri(&uri, None::<&gio::AppLaunchContext>) {
+				log::error!("Failed to open {:?}: {}", path, err);
+			}
+		};
+
+		match command {
+			Command::ReloadPlugins => App::reload_plugins(app),
+			Command::OpenPreferences => if let Some(window) = window { window.borrow_mut().show_preferences(); },
+			Command::ToggleDeveloper => if let Some(preferences) = preferences {
+				preferences.set(|prefs| prefs.developer = !prefs.developer);
+				if let Err(err) = preferences.save() { log::error!("Failed to save preferences: {}", err); }
+			},
+			Command::ClearHistory => app.borrow_mut().history.clear(),
+			Command::OpenConfig => if let Some(preferences) = preferences { open(&preferences.get().path.clone()); },
+			Command::ReloadTheme => if let Some(preferences) = preferences { preferences.refresh(); },
+			Command::ShowLogs => open(&paths::log_file())
+		}
+	}
+
+	/**
+	 * Activates the first result.
+	 * This is an associated function, as activating a result hides the window, which updates the app.
+	 */
+
+	fn submit(app: &Shared<Self>) {
+		let window = app.borrow().get_window();
+		if let Some(window) = window { window.borrow_mut().activate_result(""); }
+	}
+
+	/**
+	 * Records that a result was activated in the history, and in the query log if it is enabled.
+	 */
+
+	fn on_activate(&mut self, plugin: &str, id: &str) {
+		self.history.record(plugin, id);
+
+		let log_queries = self.preferences.as_ref().map_or(false, |preferences| preferences.get().log_queries);
+		if !log_queries || CommandPlugin::parse_query(&self.query).is_some() { return; }
+
+		let record = QueryRecord {
+			time: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+			query: self.query.clone(),
+			shown: self.shown.borrow().clone(),
+			activated: ResultKey { plugin: plugin.to_owned(), id: id.to_owned() }
+		};
+
+		if let Err(err) = query_log::append(&query_log::default_path(), &record) {
+			log::error!("Failed to write to the query log: {}", err);
+		}
 	}
 
 	fn on_input(&mut self, value: &str) {
-		let results = (self.callbacks.on_search)(value);
-		self.window.as_ref().unwrap().borrow_mut().set_results(results);
+		self.query = value.to_owned();
+
+		if let Some(query) = CommandPlugin::parse_query(value) {
+			let commands = self.commands.iter().cloned().collect();
+			self.searcher.as_mut().unwrap().search(query, commands, Boosts::default());
+			return;
+		}
+
+		let mut plugins = (self.callbacks.get_plugins)();
+		if let Some(scope) = &self.scope { plugins.retain(|plugin| &plugin.identifier == scope); }
+		let boosts = self.history.get_boosts();
+		self.searcher.as_mut().unwrap().search(value, plugins, boosts);
 	}
 
-	fn on_submit(&mut self) {
-		println!("submit");
+	fn on_visibility_changed(&mut self, visible: bool) {
+		if !visible { self.scope = None; }
+		if let Some(dbus) = &*self.dbus.borrow() { dbus.set_visible(visible); }
 	}
 }
@@ -0,0 +1,182 @@
+/**! Command-line options, which are forwarded to the running instance if there is one, and headless subcommands. */
+
+use gio::prelude::*;
+use std::path::PathBuf;
+
+/**
+ * Options parsed from the command line.
+ */
+
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+	pub toggle: bool,
+	pub show: bool,
+	pub hide: bool,
+	pub quit: bool,
+
+	pub query: Option<String>,
+	pub plugin: Option<String>,
+	pub config: Option<PathBuf>
+}
+
+impl Options {
+
+	/**
+	 * Registers the options with the application, so that they will be parsed and listed in `--help`.
+	 */
+
+	pub fn register(gtk: &gtk::Application) {
+		let add = |name: &str, short: char, arg: glib::OptionArg, description: &str, arg_description: Option<&str>|
+			gtk.add_main_option(name, glib::Char::new(short).unwrap(), glib::OptionFlags::NONE, arg, description, arg_description);
+
+		add("toggle", 't', glib::OptionArg::None, "Show the window if it is hidden, otherwise hide it", None);
+		add("show", 's', glib::OptionArg::None, "Show the window", None);
+		add("hide", '\0', glib::OptionArg::None, "Hide the window", None);
+		add("query", 'q', glib::OptionArg::String, "Show the window with the search prefilled", Some("TEXT"));
+		add("plugin", 'p', glib::OptionArg::String, "Show the window, only searching the plugin specified", Some("ID"));
+		add("config", 'c', glib::OptionArg::String, "Use a different configuration file", Some("PATH"));
+		add("quit", '\0', glib::OptionArg::None, "Quit the running instance", None);
+	}
+
+
+	/**
+	 * Reads options from a parsed options dictionary.
+	 */
+
+	pub fn from_dict(dict: &glib::VariantDict) -> Self {
+		let string = |name: &str| dict.lookup_value(name, None).and_then(|v| v.get_str().map(|s| s.to_owned()));
+
+		Options {
+			toggle: dict.contains("toggle"),
+			show: dict.contains("show"),
+			hide: dict.contains("hide"),
+			quit: dict.contains("quit"),
+
+			query: string("query"),
+			plugin: string("plugin"),
+			config: string("config").map(PathBuf::from)
+		}
+	}
+
+
+	/**
+	 * Returns whether any options that show or hide the window were provided.
+	 */
+
+	pub fn has_window_options(&self) -> bool {
+		self.toggle || self.show || self.hide || self.query.is_some() || self.plugin.is_some()
+	}
+}
+
+
+/**
+ * Subcommands, which run without a display instead of starting the app.
+ */
+
+#[derive(Debug, Clone)]
+pub enum Subcommand {
+
+	/** Queries the plugins and prints the ranked results. */
+	Search { query: String, json: bool },
+
+	/**
+	 * Replays a query log against the plugins, and reports ranking metrics for each ranking configuration.
+	 * The default configuration is used if none are provided.
+	 */
+
+	Eval { log: Option<PathBuf>, configs: Vec<PathBuf>, json: bool }
+}
+
+impl Subcommand {
+
+	/**
+	 * Parses a subcommand from the process arguments, if one was provided.
+	 * Returns an error if the subcommand's arguments are invalid, such as an unknown option.
+	 * Options are read until a `--` argument, after which every argument is positional.
+	 */
+
+	pub fn parse(args: &[String]) -> Option<Result<Self, String>> {
+		let name = args.get(1).map(|arg| arg.as_str()).filter(|name| *name == "search" || *name == "eval")?;
+		Some(Subcommand::parse_args(name, args.get(2..).unwrap_or(&[])))
+	}
+
+	fn parse_args(name: &str, rest: &[String]) -> Result<Self, String> {
+		let mut json = false;
+		let mut configs = vec![];
+		let mut positional = vec![];
+
+		let mut options = true;
+		let mut args = rest.iter();
+
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--" if options => options = false,
+				"--json" if options => json = true,
+				"--config" if options && name == "eval" =>
+					configs.push(PathBuf::from(args.next().ok_or_else(|| "--config needs a path.".to_owned())?)),
+				option if options && option.starts_with("--") => return Err(format!("Unknown option '{}' for {}.", option, name)),
+				_ => positional.push(arg.clone())
+			}
+		}
+
+		match name {
+			"search" => Ok(Subcommand::Search { query: positional.join(" "), json }),
+			_ => {
+				if positional.len() > 1 { return Err("eval takes at most one query log.".to_owned()); }
+				Ok(Subcommand::Eval { log: positional.pop().map(PathBuf::from), configs, json })
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+	use super::Subcommand;
+
+	fn parse(args: &[&str]) -> Option<Result<Subcommand, String>> {
+		let args = [ "scout" ].iter().chain(args.iter()).map(|arg| arg.to_string()).collect::<Vec<_>>();
+		Subcommand::parse(&args)
+	}
+
+	fn search(args: &[&str]) -> (String, bool) {
+		match parse(&[ &[ "search" ], args ].concat()) {
+			Some(Ok(Subcommand::Search { query, json })) => (query, json),
+			other => panic!("Expected a search, got {:?}.", other)
+		}
+	}
+
+	#[test]
+	fn ignores_other_arguments() {
+		assert!(parse(&[]).is_none());
+		assert!(parse(&[ "--toggle" ]).is_none());
+	}
+
+	#[test]
+	fn parses_search() {
+		assert_eq!(search(&[ "web", "browser" ]), ("web browser".to_owned(), false));
+		assert_eq!(search(&[ "--json", "firefox" ]), ("firefox".to_owned(), true));
+		assert_eq!(search(&[ "--", "--json", "--x" ]), ("--json --x".to_owned(), false));
+		assert_eq!(search(&[ "--json", "--", "--" ]), ("--".to_owned(), true));
+	}
+
+	#[test]
+	fn parses_eval() {
+		match parse(&[ "eval", "--config", "a.json", "queries.jsonl", "--config", "b.json", "--json" ]) {
+			Some(Ok(Subcommand::Eval { log, configs, json })) => {
+				assert_eq!(log, Some(PathBuf::from("queries.jsonl")));
+				assert_eq!(configs, vec![ PathBuf::from("a.json"), PathBuf::from("b.json") ]);
+				assert!(json);
+			},
+			other => panic!("Expected an eval, got {:?}.", other)
+		}
+	}
+
+	#[test]
+	fn rejects_invalid_arguments() {
+		assert!(matches!(parse(&[ "search", "--jsn", "firefox" ]), Some(Err(_))));
+		assert!(matches!(parse(&[ "search", "--config", "a.json" ]), Some(Err(_))));
+		assert!(matches!(parse(&[ "eval", "--config" ]), Some(Err(_))));
+		assert!(matches!(parse(&[ "eval", "a.jsonl", "b.jsonl" ]), Some(Err(_))));
+	}
+}
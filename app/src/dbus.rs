@@ -0,0 +1,382 @@
+/**! Exposes a D-Bus interface on the application's session bus connection, so other programs can control Scout. */
+
+use gio::prelude::*;
+use glib::translate::{ ToGlibPtr, FromGlibPtrFull, FromGlibPtrNone };
+use std::sync::{ Arc, Mutex };
+use std::sync::atomic::{ AtomicBool, Ordering };
+
+use crate::cli::Options;
+
+/** The name of the exported interface. */
+pub static INTERFACE: &str = "com.aurailus.Scout";
+
+static INTERFACE_XML: &str = r#"
+<node>
+	<interface name="com.aurailus.Scout">
+		<method name="Show"/>
+		<method name="Hide"/>
+		<method name="Toggle"/>
+		<method name="Search">
+			<arg type="s" name="query" direction="in"/>
+		</method>
+		<method name="GetResults">
+			<arg type="a(sss)" name="results" direction="out"/>
+		</method>
+		<method name="Activate">
+			<arg type="s" name="id" direction="in"/>
+		</method>
+		<method name="ReloadPlugins"/>
+		<signal name="VisibilityChanged">
+			<arg type="b" name="visible"/>
+		</signal>
+		<property name="Visible" type="b" access="read"/>
+	</interface>
+</node>
+"#;
+
+/**
+ * A command received over D-Bus, which is handled on the main thread.
+ */
+
+#[derive(Debug, Clone)]
+pub enum Command {
+
+	/** Show, hide, or toggle the window, or open it with a query. */
+	Window(Options),
+
+	/** Activate a displayed result by its identifier from GetResults, or the first result if the identifier is empty. */
+	Activate(String),
+
+	/** Unload and reload all plugins. */
+	ReloadPlugins
+}
+
+/**
+ * A result shown in the window, as returned by GetResults.
+ * Its identifier can be passed to Activate.
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShownResult {
+	pub plugin: String,
+	pub id: String,
+	pub title: String
+}
+
+/**
+ * The registered D-Bus object. This can be cloned to emit signals from elsewhere in the app.
+ */
+
+#[derive(Clone)]
+pub struct DBusServer {
+	connection: gio::DBusConnection,
+	path: String,
+	visible: Arc<AtomicBool>,
+	results: Arc<Mutex<Vec<ShownResult>>>
+}
+
+impl DBusServer {
+
+	/**
+	 * Registers the Scout interface on the application's object path.
+	 * Commands are passed to `on_command` on the main thread, after the method call has returned.
+	 * Returns None if the application isn't connected to the session bus.
+	 */
+
+	pub fn register<F: FnMut(Command) + 'static>(gtk: &gtk::Application, visible: bool, on_command: F) -> Option<Self> {
+		let connection = gtk.get_dbus_connection()?;
+		let path = gtk.get_dbus_object_path()?.to_string();
+		DBusServer::register_on(connection, path, visible, on_command)
+	}
+
+
+	/**
+	 * Registers the Scout interface on a connection, at the object path provided.
+	 */
+
+	pub fn register_on<F: FnMut(Command) + 'static>(connection: gio::DBusConnection, path: String,
+		visible: bool, mut on_command: F) -> Option<Self> {
+		let info = gio::DBusNodeInfo::new_for_xml(INTERFACE_XML).expect("Invalid D-Bus interface.")
+			.lookup_interface(INTERFACE).unwrap();
+
+		let (sender, receiver) = glib::MainContext::channel::<Command>(glib::PRIORITY_DEFAULT);
+		receiver.attach(None, move |command| { on_command(command); glib::Continue(true) });
+
+		let sender = Mutex::new(sender);
+		let visible = Arc::new(AtomicBool::new(visible));
+		let visible_clone = visible.clone();
+		let results = Arc::new(Mutex::new(vec![]));
+		let results_clone = results.clone();
+
+		let registered = connection.register_object(&path, &info,
+			move |_, _, _, _, method, parameters, invocation| {
+				let command = match method {
+					"Show" => Command::Window(Options { show: true, ..Options::default() }),
+					"Hide" => Command::Window(Options { hide: true, ..Options::default() }),
+					"Toggle" => Command::Window(Options { toggle: true, ..Options::default() }),
+					"Search" => Command::Window(Options { query: Some(get_string_arg(&parameters)), ..Options::default() }),
+					"GetResults" => {
+						let results = results_clone.lock().unwrap().iter().map(|res: &ShownResult|
+							new_tuple(&[ res.plugin.to_variant(), res.id.to_variant(), res.title.to_variant() ])).collect::<Vec<_>>();
+						invocation.return_value(Some(&new_tuple(&[ new_array("(sss)", &results) ])));
+						return;
+					},
+					"Activate" => Command::Activate(get_string_arg(&parameters)),
+					"ReloadPlugins" => Command::ReloadPlugins,
+					_ => {
+						invocation.return_dbus_error("org.freedesktop.DBus.Error.UnknownMethod", "Unknown method.");
+						return;
+					}
+				};
+
+				drop(sender.lock().unwrap().send(command));
+				invocation.return_value(None);
+			},
+			move |_, _, _, _, _| visible_clone.load(Ordering::SeqCst).to_variant(),
+			|_, _, _, _, _, _| false);
+
+		match registered {
+			Ok(_) => Some(DBusServer { connection, path, visible, results }),
+			Err(err) => {
+				log::error!("Failed to register D-Bus interface: {}", err);
+				None
+			}
+		}
+	}
+
+
+	/**
+	 * Updates the Visible property, emitting VisibilityChanged if it changed.
+	 */
+
+	pub fn set_visible(&self, visible: bool) {
+		if self.visible.swap(visible, Ordering::SeqCst) == visible { return; }
+
+		let parameters = new_tuple(&[ visible.to_variant() ]);
+		drop(self.connection.emit_signal(None, &self.path, INTERFACE, "VisibilityChanged", Some(&parameters)));
+	}
+
+
+	/**
+	 * Sets the results returned by GetResults, which should be the results shown in the window.
+	 */
+
+	pub fn set_results(&self, results: Vec<ShownResult>) {
+		*self.results.lock().unwrap() = results;
+	}
+}
+
+
+/**
+ * Reads the first string argument from a method call's parameter tuple.
+ * The glib bindings don't support tuples, so this calls into glib directly.
+ */
+
+fn get_string_arg(parameters: &glib::Variant) -> String {
+	get_child(parameters, 0).get_str().unwrap_or("").to_owned()
+}
+
+
+/**
+ * Gets a child of a tuple or array variant.
+ */
+
+fn get_child(variant: &glib::Variant, index: usize) -> glib::Variant {
+	unsafe { FromGlibPtrFull::from_glib_full(glib_sys::g_variant_get_child_value(variant.to_glib_none().0, index)) }
+}
+
+
+/**
+ * Creates a tuple variant, for use as signal parameters.
+ */
+
+fn new_tuple(children: &[glib::Variant]) -> glib::Variant {
+	let children: Vec<*mut glib_sys::GVariant> = children.iter().map(|child| child.to_glib_none().0).collect();
+	unsafe { FromGlibPtrNone::from_glib_none(glib_sys::g_variant_new_tuple(children.as_ptr(), children.len())) }
+}
+
+
+/**
+ * Creates an array variant of the element type provided, which is used if the array is empty.
+ */
+
+fn new_array(element_type: &str, children: &[glib::Variant]) -> glib::Variant {
+	let element_type = glib::VariantTy::new(element_type).expect("Invalid variant type.");
+	let children: Vec<*mut glib_sys::GVariant> = children.iter().map(|child| child.to_glib_none().0).collect();
+	unsafe { FromGlibPtrNone::from_glib_none(
+		glib_sys::g_variant_new_array(element_type.as_ptr(), children.as_ptr(), children.len())) }
+}
+
+
+#[cfg(test)]
+mod tests {
+	use gio::prelude::*;
+	use std::io::BufRead;
+	use std::sync::mpsc;
+
+	use glib::translate::ToGlibPtr;
+
+	use scout_core::Shared;
+	use super::{ DBusServer, Command, ShownResult, INTERFACE, new_tuple, get_child };
+
+	static PATH: &str = "/com/aurailus/scout";
+
+	/**
+	 * A private session bus, which is stopped when dropped. This requires `dbus-daemon`.
+	 */
+
+	struct PrivateBus {
+		daemon: std::process::Child,
+		address: String
+	}
+
+	impl PrivateBus {
+		fn start() -> Self {
+			let mut daemon = std::process::Command::new("dbus-daemon")
+				.args(&[ "--session", "--nofork", "--print-address=1" ])
+				.stdout(std::process::Stdio::piped())
+				.spawn().expect("Failed to start dbus-daemon, which is needed to test the D-Bus interface.");
+
+			let mut address = String::new();
+			std::io::BufReader::new(daemon.stdout.as_mut().unwrap()).read_line(&mut address).unwrap();
+			PrivateBus { daemon, address: address.trim().to_owned() }
+		}
+	}
+
+	impl Drop for PrivateBus {
+		fn drop(&mut self) {
+			self.daemon.kill().ok();
+			self.daemon.wait().ok();
+		}
+	}
+
+	fn connect(address: &str) -> gio::DBusConnection {
+		gio::DBusConnection::new_for_address_sync(address,
+			gio::DBusConnectionFlags::AUTHENTICATION_CLIENT | gio::DBusConnectionFlags::MESSAGE_BUS_CONNECTION,
+			None, None::<&gio::Cancellable>).unwrap()
+	}
+
+	fn call(client: &gio::DBusConnection, name: &str, interface: &str, method: &str,
+		args: &[glib::Variant]) -> Result<glib::Variant, glib::Error> {
+		let args = if args.is_empty() { None } else { Some(new_tuple(args)) };
+		client.call_sync(Some(name), PATH, interface, method, args.as_ref(), None,
+			gio::DBusCallFlags::NONE, 5000, None::<&gio::Cancellable>)
+	}
+
+	/**
+	 * Runs a client on its own connection and thread, while the server's main context is iterated,
+	 * as method calls are handled on the main context and the client's calls block.
+	 */
+
+	fn run_client<T: Send + 'static, F: FnOnce(&gio::DBusConnection) -> T + Send + 'static>(address: &str, client: F) -> T {
+		let (sender, receiver) = mpsc::channel();
+		let address = address.to_owned();
+		std::thread::spawn(move || sender.send(client(&connect(&address))).unwrap());
+
+		let context = glib::MainContext::ref_thread_default();
+		let result = loop {
+			match receiver.try_recv() {
+				Ok(result) => break result,
+				Err(mpsc::TryRecvError::Empty) => { context.iteration(false); std::thread::sleep(std::time::Duration::from_millis(1)); },
+				Err(mpsc::TryRecvError::Disconnected) => panic!("The D-Bus client panicked.")
+			}
+		};
+
+		while context.iteration(false) {}
+		result
+	}
+
+	fn get_visible(client: &gio::DBusConnection, name: &str) -> String {
+		call(client, name, "org.freedesktop.DBus.Properties", "Get", &[ INTERFACE.to_variant(), "Visible".to_variant() ])
+			.unwrap().to_string()
+	}
+
+	fn get_results(client: &gio::DBusConnection, name: &str) -> Vec<(String, String, String)> {
+		let results = get_child(&call(client, name, INTERFACE, "GetResults", &[]).unwrap(), 0);
+		let count = unsafe { glib_sys::g_variant_n_children(results.to_glib_none().0) };
+		(0..count).map(|index| {
+			let result = get_child(&results, index);
+			let field = |index| get_child(&result, index).get_str().unwrap().to_owned();
+			(field(0), field(1), field(2))
+		}).collect()
+	}
+
+	/**
+	 * Registers the interface on a private bus, and checks the commands that method calls are turned into.
+	 * The test runs on its own main context, which the interface's calls and commands are dispatched on.
+	 */
+
+	#[test]
+	fn handles_method_calls() {
+		glib::MainContext::new().with_thread_default(|| {
+			let bus = PrivateBus::start();
+			let connection = connect(&bus.address);
+			let name = connection.get_unique_name().unwrap().to_string();
+
+			let commands = Shared::new(vec![]);
+			let commands_clone = commands.clone();
+			let server = DBusServer::register_on(connection, PATH.to_owned(), false,
+				move |command| commands_clone.borrow_mut().push(command)).unwrap();
+
+			let name_clone = name.clone();
+			let (visible, unknown) = run_client(&bus.address, move |client| {
+				for method in &[ "Show", "Toggle", "Hide", "ReloadPlugins" ] { call(client, &name_clone, INTERFACE, method, &[]).unwrap(); }
+				call(client, &name_clone, INTERFACE, "Search", &[ "firefox".to_variant() ]).unwrap();
+				assert!(get_results(client, &name_clone).is_empty());
+
+				(get_visible(client, &name_clone), call(client, &name_clone, INTERFACE, "Launch", &[]).is_err())
+			});
+
+			assert_eq!(visible, "(<false>,)");
+			assert!(unknown, "Unknown methods should return an error.");
+
+			let commands = commands.borrow();
+			assert_eq!(commands.len(), 5, "{:?}", commands);
+			assert!(matches!(&commands[0], Command::Window(options) if options.show));
+			assert!(matches!(&commands[1], Command::Window(options) if options.toggle && !options.show));
+			assert!(matches!(&commands[2], Command::Window(options) if options.hide));
+			assert!(matches!(&commands[3], Command::ReloadPlugins));
+			assert!(matches!(&commands[4], Command::Window(options) if options.query.as_deref() == Some("firefox")));
+
+			server.set_visible(true);
+			assert_eq!(run_client(&bus.address, move |client| get_visible(client, &name)), "(<true>,)");
+		});
+	}
+
+	/**
+	 * Activates a result using the identifier returned by GetResults.
+	 * Identifiers like the application plugin's, which combine several fields, must survive the round trip.
+	 */
+
+	#[test]
+	fn activates_shown_results() {
+		glib::MainContext::new().with_thread_default(|| {
+			let bus = PrivateBus::start();
+			let connection = connect(&bus.address);
+			let name = connection.get_unique_name().unwrap().to_string();
+
+			let commands = Shared::new(vec![]);
+			let commands_clone = commands.clone();
+			let server = DBusServer::register_on(connection, PATH.to_owned(), true,
+				move |command| commands_clone.borrow_mut().push(command)).unwrap();
+
+			let shown = vec![
+				ShownResult { plugin: "applications".into(), id: "Firefox\nfirefox %u".into(), title: "Firefox".into() },
+				ShownResult { plugin: "directory".into(), id: "/home/user/Documents".into(), title: "Documents".into() }
+			];
+			server.set_results(shown.clone());
+
+			let results = run_client(&bus.address, move |client| {
+				let results = get_results(client, &name);
+				call(client, &name, INTERFACE, "Activate", &[ results[0].1.to_variant() ]).unwrap();
+				results
+			});
+
+			assert_eq!(results, shown.iter().map(|res| (res.plugin.clone(), res.id.clone(), res.title.clone())).collect::<Vec<_>>());
+
+			let commands = commands.borrow();
+			assert_eq!(commands.len(), 1, "{:?}", commands);
+			assert!(matches!(&commands[0], Command::Activate(id) if id == &shown[0].id));
+		});
+	}
+}
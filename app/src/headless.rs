@@ -0,0 +1,249 @@
+/**! Runs subcommands without a display, for scripting and testing plugins. */
+
+use crate::cli::Subcommand;
+use std::path::{ Path, PathBuf };
+
+use crate::query_log;
+use crate::history::History;
+use crate::plugin::{ PluginParser, RankingConfig };
+
+pub fn run(plugins: &PluginParser, subcommand: Subcommand) {
+	match subcommand {
+		Subcommand::Search { query, json } => search(plugins, &query, json),
+		Subcommand::Eval { log, configs, json } => eval(plugins, log.as_deref(), &configs, json)
+	}
+}
+
+/**
+ * Prints the ranked results for a query, either as JSON or as a human-readable table.
+ */
+
+fn search(plugins: &PluginParser, query: &str, json: bool) {
+	let results = PluginParser::get_results(&plugins.get_plugins(), query, &History::load().get_boosts());
+
+	if json {
+		let results = results.iter().map(|res| serde_json::json!({
+			"plugin": res.plugin,
+			"id": res.result.get_id(),
+			"title": res.result.get_title(),
+			"subtitle": res.result.get_subtitle(),
+			"score": res.result.get_score(),
+			"actions": res.result.get_actions()
+		})).collect::<Vec<_>>();
+
+		println!("{}", serde_json::to_string_pretty(&results).unwrap());
+	}
+	else {
+		for res in results.iter() {
+			println!("{:>4}  {:<12}  {}", res.result.get_score(), res.plugin, res.result.get_title());
+		}
+	}
+}
+
+
+/**
+ * Ranking metrics over a set of logged queries.
+ */
+
+#[derive(Debug, Default)]
+struct Metrics {
+	queries: usize,
+
+	/** How many activated results were returned at all. */
+	found: usize,
+	top_1: usize,
+	reciprocal_ranks: f64
+}
+
+impl Metrics {
+
+	/**
+	 * Adds a query, given the position the activated result was ranked at, if it was returned.
+	 */
+
+	fn add(&mut self, position: Option<usize>) {
+		self.queries += 1;
+		if let Some(position) = position {
+			self.found += 1;
+			if position == 0 { self.top_1 += 1; }
+			self.reciprocal_ranks += 1.0 / (position + 1) as f64;
+		}
+	}
+
+	fn get_mrr(&self) -> f64 {
+		if self.queries == 0 { 0.0 } else { self.reciprocal_ranks / self.queries as f64 }
+	}
+
+	fn get_top_1(&self) -> f64 {
+		if self.queries == 0 { 0.0 } else { self.top_1 as f64 / self.queries as f64 }
+	}
+}
+
+/**
+ * Measures how the activated results were ranked in the results that were logged with them.
+ */
+
+fn get_logged_metrics(records: &[query_log::QueryRecord]) -> Metrics {
+	let mut metrics = Metrics::default();
+	for record in records.iter() { metrics.add(record.shown.iter().position(|key| key == &record.activated)); }
+	metrics
+}
+
+/**
+ * Loads ranking configurations from JSON files, named by their file names.
+ */
+
+fn load_configs(paths: &[PathBuf]) -> Result<Vec<(String, RankingConfig)>, String> {
+	if paths.is_empty() { return Ok(vec![ ("default".to_owned(), RankingConfig::default()) ]); }
+
+	paths.iter().map(|path| {
+		let contents = std::fs::read_to_string(path).map_err(|err| format!("Failed to read {:?}: {}", path, err))?;
+		let config = serde_json::from_str(&contents).map_err(|err| format!("Failed to parse {:?}: {}", path, err))?;
+		let name = path.file_stem().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+		Ok((name, config))
+	}).collect()
+}
+
+/**
+ * Replays a query log against the current plugins, and prints the mean reciprocal rank and top-1 accuracy
+ * of the activated results for each ranking configuration, as well as for the results that were logged.
+ * History boosts are rebuilt from the log as it is replayed, so each query only benefits from earlier ones.
+ */
+
+fn eval(plugins: &PluginParser, log: Option<&Path>, configs: &[PathBuf], json: bool) {
+	let path = log.map(Path::to_owned).unwrap_or_else(query_log::default_path);
+	let records = match query_log::load(&path) {
+		Ok(records) => records,
+		Err(err) => { eprintln!("Failed to read query log {:?}: {}", path, err); std::process::exit(1); }
+	};
+
+	let configs = match load_configs(configs) {
+		Ok(configs) => configs,
+		Err(err) => { eprintln!("{}", err); std::process::exit(1); }
+	};
+
+	let mut reports = vec![ ("logged".to_owned(), get_logged_metrics(&records)) ];
+	let loaded = plugins.get_plugins();
+
+	for (name, config) in configs.iter() {
+		let mut metrics = Metrics::default();
+		let mut history = History::default();
+
+		for record in records.iter() {
+			let results = PluginParser::get_results_with(&loaded, &record.query, &history.get_boosts(), config);
+			metrics.add(results.iter().position(|res|
+				res.plugin == record.activated.plugin && res.result.get_id() == record.activated.id));
+			history.record(&record.activated.plugin, &record.activated.id);
+		}
+
+		reports.push((name.clone(), metrics));
+	}
+
+	if json {
+		let reports = reports.iter().map(|(name, metrics)| serde_json::json!({
+			"config": name,
+			"queries": metrics.queries,
+			"found": metrics.found,
+			"mrr": metrics.get_mrr(),
+			"top_1": metrics.get_top_1()
+		})).collect::<Vec<_>>();
+
+		println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+	}
+	else {
+		println!("{:<16}  {:>7}  {:>7}  {:>6}  {:>6}", "config", "queries", "found", "mrr", "top-1");
+		for (name, metrics) in reports.iter() {
+			println!("{:<16}  {:>7}  {:>7}  {:>6.3}  {:>6.3}", name, metrics.queries, metrics.found, metrics.get_mrr(), metrics.get_top_1());
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+
+	use crate::query_log::{ QueryRecord, ResultKey };
+	use super::{ Metrics, get_logged_metrics, load_configs };
+
+	fn key(id: &str) -> ResultKey {
+		ResultKey { plugin: "test".to_owned(), id: id.to_owned() }
+	}
+
+	/**
+	 * Creates a record of a query, where the results shown are identified by the ids provided.
+	 */
+
+	fn record(shown: &[&str], activated: &str) -> QueryRecord {
+		QueryRecord { time: 0, query: activated.to_owned(), shown: shown.iter().map(|id| key(id)).collect(), activated: key(activated) }
+	}
+
+	/**
+	 * Returns an empty temporary directory for a test's config files.
+	 */
+
+	fn temp_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("scout-headless-test-{}-{}", std::process::id(), name));
+		drop(std::fs::remove_dir_all(&dir));
+		std::fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	#[test]
+	fn measures_rankings() {
+		let metrics = get_logged_metrics(&[
+			record(&[ "a", "b", "c", "d" ], "a"),
+			record(&[ "a", "b", "c", "d" ], "b"),
+			record(&[ "a", "b", "c", "d" ], "d"),
+			record(&[ "b" ], "b")
+		]);
+
+		assert_eq!((metrics.queries, metrics.found, metrics.top_1), (4, 4, 2));
+		assert!((metrics.get_mrr() - (1.0 + 0.5 + 0.25 + 1.0) / 4.0).abs() < 1e-9);
+		assert!((metrics.get_top_1() - 0.5).abs() < 1e-9);
+	}
+
+	#[test]
+	fn counts_queries_without_the_activated_result() {
+		let metrics = get_logged_metrics(&[ record(&[ "a", "b" ], "c"), record(&[], "a"), record(&[ "a" ], "a") ]);
+
+		assert_eq!((metrics.queries, metrics.found, metrics.top_1), (3, 1, 1));
+		assert!((metrics.get_mrr() - 1.0 / 3.0).abs() < 1e-9);
+		assert!((metrics.get_top_1() - 1.0 / 3.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn measures_nothing_without_queries() {
+		let metrics = Metrics::default();
+		assert_eq!(metrics.get_mrr(), 0.0);
+		assert_eq!(metrics.get_top_1(), 0.0);
+	}
+
+	#[test]
+	fn loads_configs() {
+		let configs = load_configs(&[]).unwrap();
+		assert_eq!(configs.len(), 1);
+		assert_eq!(configs[0].0, "default");
+
+		let dir = temp_dir("configs");
+		std::fs::write(dir.join("strict.json"), r#"{ "cutoff": 0.9 }"#).unwrap();
+
+		let configs = load_configs(&[ dir.join("strict.json") ]).unwrap();
+		assert_eq!(configs[0].0, "strict");
+		assert_eq!(configs[0].1.cutoff, 0.9);
+		assert_eq!(configs[0].1.history_weight, 1.0);
+	}
+
+	#[test]
+	fn fails_to_load_invalid_configs() {
+		let dir = temp_dir("invalid");
+		std::fs::write(dir.join("valid.json"), "{}").unwrap();
+		std::fs::write(dir.join("invalid.json"), r#"{ "cutoff": "high" }"#).unwrap();
+
+		let err = load_configs(&[ dir.join("valid.json"), dir.join("invalid.json") ]).unwrap_err();
+		assert!(err.starts_with("Failed to parse") && err.contains("invalid.json"), "{}", err);
+
+		let err = load_configs(&[ dir.join("missing.json") ]).unwrap_err();
+		assert!(err.starts_with("Failed to read") && err.contains("missing.json"), "{}", err);
+	}
+}
@@ -0,0 +1,138 @@
+/**! Records which results have been activated, so frequently used results can be found quickly. */
+
+use serde::{ Serialize, Deserialize };
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::paths;
+
+/** The score added to a result for each time it has been activated. */
+static BOOST_PER_USE: usize = 2;
+
+/** How many activations count towards a result's boost. */
+static MAX_BOOSTED_USES: u32 = 10;
+
+/** How long since a result was last used before its boost is halved, in seconds. */
+static STALE_AFTER: u64 = 30 * 24 * 60 * 60;
+
+/**
+ * How often, and how recently, a result has been activated.
+ */
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct HistoryEntry {
+	pub count: u32,
+
+	/** When the result was last activated, in seconds since the Unix epoch. */
+	pub last_used: u64
+}
+
+/**
+ * The activation history of results, keyed by plugin and result identifier.
+ * This is stored in `$XDG_DATA_HOME/scout/history.json`.
+ */
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct History {
+	/** Where the history is saved, or None if it is only kept in memory. */
+	#[serde(skip)]
+	path: Option<PathBuf>,
+
+	entries: HashMap<String, HistoryEntry>
+}
+
+impl History {
+
+	/**
+	 * Loads the history, or creates an empty one if it doesn't exist or can't be read.
+	 */
+
+	pub fn load() -> Self {
+		let path = paths::data_dir().join("history.json");
+
+		let mut history = std::fs::read_to_string(&path).ok()
+			.and_then(|contents| serde_json::from_str::<History>(&contents)
+				.map_err(|err| log::warn!("Error reading history, starting a new one. {}", err)).ok())
+			.unwrap_or_default();
+
+		history.path = Some(path);
+		history.migrate();
+		history
+	}
+
+	/**
+	 * Rewrites the keys of application results recorded by older versions, which separated
+	 * the application's name and command with a NUL instead of a newline.
+	 */
+
+	fn migrate(&mut self) {
+		let prefix = "application\0";
+		let old = self.entries.keys()
+			.filter(|key| key.starts_with(prefix) && key[prefix.len()..].contains('\0')).cloned().collect::<Vec<_>>();
+
+		for key in old {
+			let entry = self.entries.remove(&key).unwrap();
+			self.entries.insert([ prefix, &key[prefix.len()..].replacen('\0', "\n", 1) ].join(""), entry);
+		}
+	}
+
+	fn get_key(plugin: &str, id: &str) -> String {
+		[ plugin, "\0", id ].join("")
+	}
+
+	/**
+	 * Returns the score boost for every activated result, which can be sent to the search thread.
+	 * Results get a boost for each use, up to a limit, which is halved if they haven't been used recently.
+	 */
+
+	pub fn get_boosts(&self) -> Boosts {
+		let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+		Boosts(self.entries.iter().map(|(key, entry)| {
+			let boost = entry.count.min(MAX_BOOSTED_USES) as usize * BOOST_PER_USE;
+			(key.clone(), if now.saturating_sub(entry.last_used) > STALE_AFTER { boost / 2 } else { boost })
+		}).collect())
+	}
+
+	/**
+	 * Records that a result was activated, and saves the history.
+	 */
+
+	pub fn record(&mut self, plugin: &str, id: &str) {
+		let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+		let entry = self.entries.entry(History::get_key(plugin, id)).or_default();
+		entry.count += 1;
+		entry.last_used = now;
+		self.save();
+	}
+
+	/**
+	 * Forgets every activated result, and saves the history.
+	 */
+
+	pub fn clear(&mut self) {
+		self.entries.clear();
+		self.save();
+	}
+
+	fn save(&self) {
+		let path = match &self.path { Some(path) => path, None => return };
+		let saved = serde_json::to_string(&self)
+			.map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+			.and_then(|contents| paths::write_atomic(path, contents.as_bytes()));
+		if let Err(err) = saved { log::error!("Failed to save history: {}", err); }
+	}
+}
+
+
+/**
+ * A snapshot of the score boosts for activated results.
+ */
+
+#[derive(Debug, Clone, Default)]
+pub struct Boosts(HashMap<String, usize>);
+
+impl Boosts {
+	pub fn get(&self, plugin: &str, id: &str) -> usize {
+		self.0.get(&History::get_key(plugin, id)).cloned().unwrap_or(0)
+	}
+}
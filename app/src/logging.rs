@@ -0,0 +1,179 @@
+/**! Scout's logger, which writes to stderr and a rotating log file, and keeps recent lines for the log viewer. */
+
+use std::io::Write;
+use std::sync::{ Arc, Mutex };
+use std::collections::VecDeque;
+use once_cell::sync::OnceCell;
+
+use crate::paths;
+
+/** The size a log file may grow to before it is rotated, in bytes. */
+static MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/** How many rotated log files are kept, as `scout.log.1` to `scout.log.N`. */
+static MAX_FILES: usize = 3;
+
+/** How many recent lines are kept in memory for the log viewer. */
+pub static MAX_LINES: usize = 1000;
+
+/**
+ * A logged message.
+ */
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+	pub time: String,
+	pub level: log::Level,
+
+	/** The module that logged the message, or `plugin:<id>` for plugins. */
+	pub target: String,
+	pub message: String
+}
+
+impl std::fmt::Display for LogLine {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{} {:<5} [{}] {}", self.time, self.level, self.target, self.message)
+	}
+}
+
+struct LogFile {
+	file: Option<std::fs::File>,
+	size: u64
+}
+
+pub struct Logger {
+	level: log::LevelFilter,
+	file: Mutex<LogFile>,
+	lines: Mutex<VecDeque<LogLine>>,
+	subscribers: Mutex<Vec<glib::Sender<LogLine>>>
+}
+
+impl Logger {
+
+	/**
+	 * Opens the log file for appending, creating the state directory if needed.
+	 */
+
+	fn open_file() -> LogFile {
+		let path = paths::log_file();
+		if let Some(parent) = path.parent() { drop(std::fs::create_dir_all(parent)); }
+
+		match std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+			Ok(file) => LogFile { size: file.metadata().map(|m| m.len()).unwrap_or(0), file: Some(file) },
+			Err(err) => {
+				eprintln!("Failed to open log file {:?}: {}", path, err);
+				LogFile { file: None, size: 0 }
+			}
+		}
+	}
+
+	/**
+	 * Moves `scout.log` to `scout.log.1`, shifting older files up and deleting the oldest.
+	 */
+
+	fn rotate(file: &mut LogFile) {
+		let path = paths::log_file();
+		let numbered = |i: usize| { let mut name = path.clone().into_os_string(); name.push(format!(".{}", i)); name };
+
+		file.file = None;
+		drop(std::fs::remove_file(numbered(MAX_FILES)));
+		for i in (1..MAX_FILES).rev() { drop(std::fs::rename(numbered(i), numbered(i + 1))); }
+		drop(std::fs::rename(&path, numbered(1)));
+
+		*file = Logger::open_file();
+	}
+
+	fn write_file(&self, line: &str) {
+		let mut file = self.file.lock().unwrap();
+		if file.size >= MAX_FILE_SIZE { Logger::rotate(&mut file); }
+
+		let written = match file.file.as_mut() { Some(f) => writeln!(f, "{}", line).is_ok(), None => false };
+		if written { file.size += line.len() as u64 + 1; }
+	}
+}
+
+impl log::Log for Logger {
+	fn enabled(&self, metadata: &log::Metadata) -> bool {
+		metadata.level() <= self.level
+	}
+
+	fn log(&self, record: &log::Record) {
+		if !self.enabled(record.metadata()) { return; }
+
+		let line = LogLine {
+			time: glib::DateTime::new_now_local().format("%F %T").map(|time| time.to_string()).unwrap_or_default(),
+			level: record.level(),
+			target: record.target().to_owned(),
+			message: record.args().to_string()
+		};
+
+		let formatted = line.to_string();
+		eprintln!("{}", formatted);
+		self.write_file(&formatted);
+
+		let mut lines = self.lines.lock().unwrap();
+		if lines.len() >= MAX_LINES { lines.pop_front(); }
+		lines.push_back(line.clone());
+		drop(lines);
+
+		self.subscribers.lock().unwrap().retain(|subscriber| subscriber.send(line.clone()).is_ok());
+	}
+
+	fn flush(&self) {
+		if let Some(file) = self.file.lock().unwrap().file.as_mut() { drop(file.flush()); }
+	}
+}
+
+
+/** The installed logger, which is shared with plugins. */
+static LOGGER: OnceCell<Arc<Logger>> = OnceCell::new();
+
+/**
+ * Installs Scout's logger. The level can be set with the `SCOUT_LOG` environment variable, and defaults to `info`.
+ */
+
+pub fn init() {
+	get_logger();
+}
+
+/**
+ * Returns the installed logger, installing it if it hasn't been yet.
+ */
+
+pub fn get_logger() -> &'static Arc<Logger> {
+	LOGGER.get_or_init(|| {
+		let level = std::env::var("SCOUT_LOG").ok().and_then(|level| level.parse().ok()).unwrap_or(log::LevelFilter::Info);
+		let logger = Arc::new(Logger {
+			level,
+			file: Mutex::new(Logger::open_file()),
+			lines: Mutex::new(VecDeque::new()),
+			subscribers: Mutex::new(vec![])
+		});
+
+		log::set_boxed_logger(Box::new(SharedLogger(logger.clone()))).expect("Failed to install the logger.");
+		log::set_max_level(level);
+		logger
+	})
+}
+
+/**
+ * Returns the recent lines, and a receiver for lines logged from now on.
+ * The receiver must be attached to the main context to receive lines.
+ */
+
+pub fn subscribe() -> (Vec<LogLine>, glib::Receiver<LogLine>) {
+	let (sender, receiver) = glib::MainContext::channel::<LogLine>(glib::PRIORITY_LOW);
+	let logger = get_logger();
+	let lines = logger.lines.lock().unwrap().iter().cloned().collect();
+	logger.subscribers.lock().unwrap().push(sender);
+	(lines, receiver)
+}
+
+/** Allows the shared logger to be installed as the global logger. */
+struct SharedLogger(Arc<Logger>);
+
+impl log::Log for SharedLogger {
+	fn enabled(&self, metadata: &log::Metadata) -> bool { self.0.enabled(metadata) }
+	fn log(&self, record: &log::Record) { self.0.log(record) }
+	fn flush(&self) { self.0.flush() }
+}
@@ -5,32 +5,131 @@ static ALLOCATOR: System = System;
 use gio::prelude::*;
 
 mod app;
+mod cli;
+mod dbus;
 mod window;
+mod headless;
+mod history;
+mod logging;
+mod paths;
 mod plugin;
 mod preferences;
+mod query_log;
+mod session;
 
+use scout_core::Shared;
 use plugin::PluginParser;
 use app::{ App, AppCallbacks };
 
+/**
+ * Loads the builtin and library plugins.
+ */
+
+/**
+ * Registers the builtin plugins, and loads the plugin libraries found in the plugin directories.
+ * A library found in more than one directory is only loaded from the first, and libraries that fail to load are skipped.
+ */
+
+fn load_plugins(plugins: &Shared<PluginParser>, app: &Shared<App>) {
+	plugins.borrow_mut().add_builtin("system", std::sync::Arc::new(plugin::SystemPlugin::new()));
+
+	let mut loaded = std::collections::HashSet::new();
+	for dir in paths::plugin_dirs() {
+		let mut libraries = match std::fs::read_dir(&dir) {
+			Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).filter(|path| {
+				let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+				name.starts_with("libscout_plugin_") && name.ends_with(".so")
+			}).collect::<Vec<_>>(),
+			Err(_) => continue
+		};
+		libraries.sort();
+
+		for path in libraries {
+			if !loaded.insert(path.file_name().unwrap_or_default().to_owned()) { continue; }
+			if let Err(err) = plugins.borrow_mut().load(app, &path) { log::error!("Failed to load plugin {:?}: {}", path, err); }
+		}
+	}
+}
+
 fn main() {
+	logging::init();
+
 	let app = App::new();
 	let plugins = PluginParser::new();
 
 	let plugins_clone = plugins.clone();
+	let plugins_reload = plugins.clone();
+	let app_reload = app.clone();
 	app.borrow_mut().bind(AppCallbacks {
-		on_search: Box::new(move |query| plugins_clone.borrow_mut().get_results(query))
+		get_plugins: Box::new(move || plugins_clone.borrow().get_plugins()),
+		reload_plugins: std::rc::Rc::new(move || plugins_reload.borrow_mut().reload(&app_reload))
 	});
 
-	plugins.borrow_mut().load(&app, "target/debug/libscout_plugin_application.so").expect("Invocation Failed");
-	plugins.borrow_mut().load(&app, "target/debug/libscout_plugin_directory.so").expect("Invocation Failed");
+	let args = std::env::args().collect::<Vec<_>>();
+	if let Some(subcommand) = cli::Subcommand::parse(&args) {
+		let subcommand = match subcommand {
+			Ok(subcommand) => subcommand,
+			Err(err) => {
+				eprintln!("{}", err);
+				std::process::exit(2);
+			}
+		};
 
-	let gtk = gtk::Application::new(Some("com.aurailus.scout"), Default::default())
+		load_plugins(&plugins, &app);
+		app.borrow().flush_tasks();
+		headless::run(&plugins.borrow(), subcommand);
+		return;
+	}
+
+	let gtk = gtk::Application::new(Some("com.aurailus.scout"), gio::ApplicationFlags::HANDLES_COMMAND_LINE)
 		.expect("Failed to initialize GTK application.");
+	cli::Options::register(&gtk);
+
+	let config = Shared::new(None);
+	let window_options = Shared::new(false);
+	let config_clone = config.clone();
+	let window_options_clone = window_options.clone();
+	gtk.connect_handle_local_options(move |_, dict| {
+		let options = cli::Options::from_dict(dict);
+		window_options_clone.replace(options.has_window_options());
+		config_clone.replace(options.config);
+		-1
+	});
+
+	// When started through D-Bus activation, the window should stay hidden until it's requested.
+	// When started with window options, the options decide whether to show it, once it has been created.
+	let service = args.iter().any(|arg| arg == "--gapplication-service");
 
 	let app_clone = app.clone();
 	gtk.connect_startup(move |gtk| {
+		// Plugins are only loaded by the primary instance, other instances forward their options to it.
+		load_plugins(&plugins, &app_clone);
+
 		let bind = app_clone.clone();
-		app_clone.borrow_mut().init(gtk, &bind);
+		let visible = !service && !*window_options.borrow();
+		app_clone.borrow_mut().init(gtk, &bind, config.borrow().as_deref(), visible);
+
+		let app = app_clone.clone();
+		let gtk_clone = gtk.clone();
+		let visible = app_clone.borrow().get_window().map_or(false, |window| window.borrow().is_visible());
+		let server = dbus::DBusServer::register(gtk, visible, move |command| match command {
+			dbus::Command::Window(options) => App::handle_options(&app, &gtk_clone, &options),
+			dbus::Command::Activate(id) => {
+				let window = app.borrow().get_window();
+				if let Some(window) = window { window.borrow_mut().activate_result(&id); }
+			},
+			dbus::Command::ReloadPlugins => App::reload_plugins(&app)
+		});
+
+		if let Some(server) = server { app_clone.borrow_mut().set_dbus(server); }
 	});
-	gtk.run(&[]);
+
+	let app_clone = app.clone();
+	gtk.connect_command_line(move |gtk, command_line| {
+		let options = command_line.get_options_dict().map(|dict| cli::Options::from_dict(&dict)).unwrap_or_default();
+		App::handle_options(&app_clone, gtk, &options);
+		0
+	});
+
+	gtk.run(&args);
 }
@@ -0,0 +1,98 @@
+/**! Locates the directories Scout stores its files in, following the XDG base directory specification. */
+
+use std::path::PathBuf;
+
+/**
+ * Returns the directory specified by an XDG environment variable, or the fallback relative to the home directory.
+ * Relative paths in the variable are ignored, as the specification requires.
+ */
+
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+	std::env::var_os(var)
+		.map(PathBuf::from)
+		.filter(|path| path.is_absolute())
+		.unwrap_or_else(|| home_dir().join(fallback))
+}
+
+/**
+ * Returns the user's home directory.
+ */
+
+pub fn home_dir() -> PathBuf {
+	std::env::var_os("HOME")
+		.map(PathBuf::from)
+		.unwrap_or_else(|| PathBuf::from([ "/home/", &whoami::username() ].join("")))
+}
+
+/**
+ * Returns Scout's configuration directory, `$XDG_CONFIG_HOME/scout`.
+ */
+
+pub fn config_dir() -> PathBuf {
+	xdg_dir("XDG_CONFIG_HOME", ".config").join("scout")
+}
+
+/**
+ * Returns Scout's data directory, `$XDG_DATA_HOME/scout`.
+ */
+
+pub fn data_dir() -> PathBuf {
+	xdg_dir("XDG_DATA_HOME", ".local/share").join("scout")
+}
+
+/**
+ * Returns Scout's cache directory, `$XDG_CACHE_HOME/scout`, for data that can be recreated.
+ */
+
+pub fn cache_dir() -> PathBuf {
+	xdg_dir("XDG_CACHE_HOME", ".cache").join("scout")
+}
+
+/**
+ * Returns Scout's state directory, `$XDG_STATE_HOME/scout`, where logs are kept.
+ */
+
+pub fn state_dir() -> PathBuf {
+	xdg_dir("XDG_STATE_HOME", ".local/state").join("scout")
+}
+
+/**
+ * Returns the directories plugins are loaded from, in order of precedence:
+ * the directory containing the executable, so a build loads the plugins built beside it,
+ * `$XDG_DATA_HOME/scout/plugins`, and the system-wide `/usr/lib/scout/plugins`.
+ */
+
+pub fn plugin_dirs() -> Vec<PathBuf> {
+	let exe_dir = std::env::current_exe().ok().and_then(|exe| exe.parent().map(PathBuf::from));
+	exe_dir.into_iter().chain(vec![ data_dir().join("plugins"), PathBuf::from("/usr/lib/scout/plugins") ]).collect()
+}
+
+/**
+ * Returns the path of Scout's log file.
+ */
+
+pub fn log_file() -> PathBuf {
+	state_dir().join("scout.log")
+}
+
+/**
+ * Writes a file atomically, by writing to a temporary file beside it and renaming it into place.
+ * The parent directory is created if it doesn't exist.
+ */
+
+pub fn write_atomic(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+	use std::io::Write;
+
+	if let Some(parent) = path.parent() { std::fs::create_dir_all(parent)?; }
+
+	let mut temp_name = path.file_name().unwrap_or_default().to_owned();
+	temp_name.push(".tmp");
+	let temp = path.with_file_name(temp_name);
+
+	let mut file = std::fs::File::create(&temp)?;
+	file.write_all(contents)?;
+	file.sync_all()?;
+	drop(file);
+
+	std::fs::rename(&temp, path)
+}
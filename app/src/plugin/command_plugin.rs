@@ -0,0 +1,219 @@
+/**! A built-in plugin, which provides commands for controlling Scout itself when the query starts with `:`. */
+
+use gtk::prelude::*;
+use std::sync::Mutex;
+
+use scout_core::{ Plugin, SearchResult };
+use super::system_plugin::SystemPlugin;
+
+/** The prefix that limits a query to Scout's commands. */
+pub static COMMAND_PREFIX: &str = ":";
+
+/**
+ * A command that operates on Scout itself, which is run on the main thread.
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+	ReloadPlugins,
+	OpenPreferences,
+	ToggleDeveloper,
+	ClearHistory,
+	OpenConfig,
+	ReloadTheme,
+	ShowLogs
+}
+
+impl Command {
+	pub const ALL: [Command; 7] = [ Command::ReloadPlugins, Command::OpenPreferences, Command::ToggleDeveloper,
+		Command::ClearHistory, Command::OpenConfig, Command::ReloadTheme, Command::ShowLogs ];
+
+	pub fn get_name(&self) -> &'static str {
+		match self {
+			Command::ReloadPlugins => "reload-plugins",
+			Command::OpenPreferences => "preferences",
+			Command::ToggleDeveloper => "toggle-developer",
+			Command::ClearHistory => "clear-history",
+			Command::OpenConfig => "open-config",
+			Command::ReloadTheme => "reload-theme",
+			Command::ShowLogs => "show-logs"
+		}
+	}
+
+	pub fn get_label(&self) -> &'static str {
+		match self {
+			Command::ReloadPlugins => "Reload Plugins",
+			Command::OpenPreferences => "Open Preferences",
+			Command::ToggleDeveloper => "Toggle Developer Mode",
+			Command::ClearHistory => "Clear History",
+			Command::OpenConfig => "Open Config File",
+			Command::ReloadTheme => "Reload Theme",
+			Command::ShowLogs => "Show Logs"
+		}
+	}
+
+	pub fn get_description(&self) -> &'static str {
+		match self {
+			Command::ReloadPlugins => "Unload every plugin and load them again",
+			Command::OpenPreferences => "Open the preferences window",
+			Command::ToggleDeveloper => "Show or hide the developer preferences",
+			Command::ClearHistory => "Forget which results have been used",
+			Command::OpenConfig => "Edit the configuration file in the default editor",
+			Command::ReloadTheme => "Reload the current theme from disk",
+			Command::ShowLogs => "Open Scout's log file"
+		}
+	}
+
+	pub fn get_icon(&self) -> &'static str {
+		match self {
+			Command::ReloadPlugins => "view-refresh-symbolic",
+			Command::OpenPreferences => "preferences-system-symbolic",
+			Command::ToggleDeveloper => "applications-engineering-symbolic",
+			Command::ClearHistory => "edit-clear-all-symbolic",
+			Command::OpenConfig => "document-edit-symbolic",
+			Command::ReloadTheme => "applications-graphics-symbolic",
+			Command::ShowLogs => "utilities-terminal-symbolic"
+		}
+	}
+}
+
+/**
+ * Provides Scout's commands as results. Activating a result sends its command to the main thread.
+ */
+
+pub struct CommandPlugin {
+	sender: Mutex<glib::Sender<Command>>
+}
+
+impl CommandPlugin {
+
+	/**
+	 * Creates the plugin, and the receiver its commands will be sent to.
+	 */
+
+	pub fn new() -> (Self, glib::Receiver<Command>) {
+		let (sender, receiver) = glib::MainContext::channel::<Command>(glib::PRIORITY_DEFAULT);
+		(CommandPlugin { sender: Mutex::new(sender) }, receiver)
+	}
+
+	/**
+	 * Returns whether a query is for Scout's commands, and the query without its prefix.
+	 */
+
+	pub fn parse_query(query: &str) -> Option<&str> {
+		if query.starts_with(COMMAND_PREFIX) { Some(query[COMMAND_PREFIX.len()..].trim()) } else { None }
+	}
+}
+
+impl Plugin for CommandPlugin {
+	fn get_results(&self, query: &str) -> scout_core::Result<Vec<Box<dyn SearchResult>>> {
+		let sender = self.sender.lock().unwrap();
+
+		Ok(Command::ALL.iter().map(|command| {
+			let score = if query.is_empty() { 1 } else {
+				SystemPlugin::score(command.get_label(), query).max(SystemPlugin::score(command.get_name(), query)) };
+			Box::new(CommandResult { command: *command, score, sender: sender.clone() }) as Box<dyn SearchResult>
+		}).filter(|result| result.get_score() > 0).collect())
+	}
+}
+
+
+/**
+ * A search result for one of Scout's commands.
+ */
+
+pub struct CommandResult {
+	command: Command,
+	score: usize,
+	sender: glib::Sender<Command>
+}
+
+impl SearchResult for CommandResult {
+	fn get_score(&self) -> usize {
+		self.score
+	}
+
+	fn get_id(&self) -> String {
+		self.command.get_name().to_owned()
+	}
+
+	fn get_title(&self) -> String {
+		self.command.get_label().to_owned()
+	}
+
+	fn get_subtitle(&self) -> String {
+		self.command.get_description().to_owned()
+	}
+
+	fn get_actions(&self) -> Vec<String> {
+		vec![]
+	}
+
+	fn set_first(&self, widget: &gtk::Widget, first: bool) -> () {
+		let widget = widget.downcast_ref::<gtk::Box>().unwrap();
+		widget.get_children()[0].set_can_focus(!first);
+	}
+
+	fn activate(&self) {
+		self.sender.send(self.command).ok();
+	}
+
+	fn get_result_widget(&self) -> gtk::Widget {
+		let widget = gtk::Box::new(gtk::Orientation::Vertical, 0);
+		widget.get_style_context().add_class("Command");
+		widget.set_widget_name("SearchResult");
+
+		let button = gtk::Button::new();
+		button.get_style_context().add_class("flat");
+		widget.pack_start(&button, true, true, 0);
+
+		let command = self.command;
+		let sender = self.sender.clone();
+		button.connect_clicked(move |_| { sender.send(command).ok(); });
+
+		let widget_top = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+		button.add(&widget_top);
+
+		let icon = gtk::Image::from_icon_name(Some(self.command.get_icon()), gtk::IconSize::LargeToolbar);
+		icon.set_size_request(32, 32);
+		widget_top.pack_start(&icon, false, false, 4);
+
+		let description_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+		widget_top.pack_start(&description_box, true, true, 0);
+
+		let name_label = gtk::Label::new(Some(&[ "<span size='small' weight='bold'>",
+			COMMAND_PREFIX, self.command.get_name(), "</span>" ].join("")));
+		name_label.set_use_markup(true);
+		name_label.set_xalign(0.0);
+		description_box.pack_start(&name_label, false, false, 1);
+
+		let label = gtk::Label::new(Some(self.command.get_label()));
+		label.set_ellipsize(pango::EllipsizeMode::End);
+		label.set_xalign(0.0);
+		description_box.pack_start(&label, false, false, 1);
+
+		widget.upcast()
+	}
+
+	fn get_preview_widget(&self) -> gtk::Widget {
+		let widget = gtk::Box::new(gtk::Orientation::Vertical, 4);
+		widget.get_style_context().add_class("Command");
+		widget.set_widget_name("SearchPreview");
+		widget.set_border_width(36);
+
+		let icon = gtk::Image::from_icon_name(Some(self.command.get_icon()), gtk::IconSize::Dialog);
+		icon.set_pixel_size(96);
+		widget.pack_start(&icon, false, false, 0);
+
+		let label = gtk::Label::new(Some(self.command.get_label()));
+		widget.pack_start(&label, false, false, 4);
+
+		let description = gtk::Label::new(Some(&[ self.command.get_description(), "." ].join("")));
+		description.set_line_wrap(true);
+		description.set_justify(gtk::Justification::Center);
+		description.set_max_width_chars(36);
+		widget.pack_start(&description, false, false, 0);
+
+		widget.upcast()
+	}
+}
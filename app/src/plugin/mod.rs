@@ -5,3 +5,17 @@ pub use plugin_bindings::*;
 
 mod plugin_parser;
 pub use plugin_parser::*;
+
+pub mod profiler;
+
+mod plugin_tasks;
+pub use plugin_tasks::*;
+
+mod plugin_searcher;
+pub use plugin_searcher::*;
+
+mod system_plugin;
+pub use system_plugin::*;
+
+mod command_plugin;
+pub use command_plugin::*;
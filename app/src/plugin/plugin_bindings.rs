@@ -1,39 +1,81 @@
 /**! Provides methods to give a plugin access to the application. */
 
-use std::rc::Rc;
-use scout_core::{ Shared, Plugin, InvocationError };
+use std::sync::Arc;
+use scout_core::{ Shared, Plugin, PluginLogger, PluginStorage, Notifier, Updater, Tasks, InvocationError };
 
+use crate::paths;
 use crate::app::App;
 // use super::plugin_proxy::PluginProxy;
 
+/** How much a plugin may store in its data directory before a warning is logged, in bytes. */
+static STORAGE_QUOTA: u64 = 10 * 1024 * 1024;
+
 pub struct PluginBindings {
 	app: Shared<App>,
 	identifier: String,
-	plugin: Option<Shared<Box<dyn Plugin>>>,
-	_lib: Rc<libloading::Library>
+	plugin: Option<Arc<dyn Plugin>>,
+
+	/** The plugin's storage, which is created when it registers, so every handle to it shares the same values. */
+	storage: Option<PluginStorage>,
+	_lib: Arc<libloading::Library>
 }
 
 impl PluginBindings {
-	pub fn new(app: Shared<App>, _lib: Rc<libloading::Library>) -> PluginBindings {
-		PluginBindings { app, _lib, plugin: None, identifier: "".to_owned() }
+	pub fn new(app: Shared<App>, _lib: Arc<libloading::Library>) -> PluginBindings {
+		PluginBindings { app, _lib, plugin: None, storage: None, identifier: "".to_owned() }
+	}
+
+	/**
+	 * Creates the storage for a plugin identifier, in `plugins/<identifier>` in the data and cache directories.
+	 */
+
+	fn create_storage(identifier: &str) -> PluginStorage {
+		// Only allow characters that can't escape the plugins directory.
+		let mut name = identifier.chars().map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect::<String>();
+		if name.is_empty() { name.push('_'); }
+		PluginStorage::new(paths::data_dir().join("plugins").join(&name), paths::cache_dir().join("plugins").join(&name), STORAGE_QUOTA)
 	}
 }
 
 impl scout_core::PluginBindings for PluginBindings {
 	fn register(&mut self, identifier: &str, plugin: Box<dyn Plugin>) {
 		if self.plugin.is_some() {
-			println!("[WARN] Plugin called register twice, '{}' -> '{}'.", self.identifier, identifier);
+			log::warn!("Plugin called register twice, '{}' -> '{}'.", self.identifier, identifier);
 		}
 
 		self.identifier = identifier.to_owned();
-		self.plugin = Some(Shared::new(plugin));
+		self.plugin = Some(Arc::from(plugin));
+		self.storage = Some(PluginBindings::create_storage(identifier));
 	}
 
 	fn add_stylesheet(&mut self, stylesheet: &'static str) {
 		self.app.borrow_mut().add_stylesheet(stylesheet);
 	}
 
-	fn get_plugin(&self) -> Result<(&str, Shared<Box<dyn Plugin>>), InvocationError> {
+	fn get_logger(&self) -> PluginLogger {
+		PluginLogger::new(&self.identifier, crate::logging::get_logger().clone())
+	}
+
+	fn get_notifier(&self) -> Notifier {
+		self.app.borrow().get_notifier(&self.identifier)
+	}
+
+	fn get_updater(&self) -> Updater {
+		self.app.borrow().get_updater(&self.identifier)
+	}
+
+	fn get_tasks(&self) -> Tasks {
+		self.app.borrow().get_tasks(&self.identifier, Some(&self._lib))
+	}
+
+	fn get_storage(&self) -> PluginStorage {
+		self.storage.clone().unwrap_or_else(|| {
+			log::warn!("A plugin requested storage before registering.");
+			PluginBindings::create_storage(&self.identifier)
+		})
+	}
+
+	fn get_plugin(&self) -> Result<(&str, Arc<dyn Plugin>), InvocationError> {
 		if self.plugin.is_some() {
 			Ok((&self.identifier, self.plugin.as_ref().unwrap().clone()))
 		}
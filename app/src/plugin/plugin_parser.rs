@@ -1,20 +1,97 @@
 /**! Parses plugins and stores them, providing methods to retrieve data from them. */
 
-use std::rc::Rc;
+use std::sync::Arc;
 use scout_core::{ Shared, SearchResult };
 
 use crate::app::App;
+use crate::history::Boosts;
+use super::profiler;
 use super::plugin_bindings::PluginBindings;
 
+/**
+ * A registered plugin, along with the library that provides it, if it isn't built in.
+ * This can be cloned cheaply and sent to worker threads.
+ */
+
+#[derive(Clone)]
+pub struct LoadedPlugin {
+	pub identifier: String,
+	pub plugin: Arc<dyn scout_core::Plugin>,
+	_lib: Option<Arc<libloading::Library>>
+}
+
+impl LoadedPlugin {
+
+	/**
+	 * Wraps a plugin that is built into the app.
+	 */
+
+	pub fn builtin(identifier: &str, plugin: Arc<dyn scout_core::Plugin>) -> Self {
+		LoadedPlugin { identifier: identifier.to_owned(), plugin, _lib: None }
+	}
+
+	/**
+	 * Wraps a result from the plugin, keeping its library loaded as long as the result exists.
+	 */
+
+	pub fn wrap(&self, result: Box<dyn SearchResult>) -> PluginResult {
+		PluginResult { plugin: self.identifier.clone(), result, _lib: self._lib.clone() }
+	}
+}
+
+/**
+ * A search result, along with the identifier of the plugin that provided it.
+ */
+
+pub struct PluginResult {
+	pub plugin: String,
+	pub result: Box<dyn SearchResult>,
+	_lib: Option<Arc<libloading::Library>>
+}
+
+fn default_cutoff() -> f64 { 0.75 }
+
+fn default_history_weight() -> f64 { 1.0 }
+
+/**
+ * Parameters for ranking results, which can be loaded from JSON to compare configurations with `scout eval`.
+ */
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct RankingConfig {
+
+	/** Results scoring under this fraction of the top result's score are dropped. */
+	#[serde(default = "default_cutoff")]
+	pub cutoff: f64,
+
+	/** How much history boosts are multiplied by before they are added to scores. */
+	#[serde(default = "default_history_weight")]
+	pub history_weight: f64
+}
+
+impl Default for RankingConfig {
+	fn default() -> Self { RankingConfig { cutoff: default_cutoff(), history_weight: default_history_weight() } }
+}
+
 #[derive(Default)]
 pub struct PluginParser {
-	plugins: Vec<Shared<Box<dyn scout_core::Plugin>>>,
+	paths: Vec<std::ffi::OsString>,
+	builtins: Vec<LoadedPlugin>,
+	plugins: Vec<LoadedPlugin>,
 	_bindings: Vec<Shared<Box<dyn scout_core::PluginBindings>>>
 }
 
 impl PluginParser {
 	pub fn new() -> Shared<PluginParser> { Shared::new(PluginParser::default()) }
 
+	/**
+	 * Adds a plugin that is built into the app. Built in plugins are kept when plugins are reloaded.
+	 */
+
+	pub fn add_builtin(&mut self, identifier: &str, plugin: Arc<dyn scout_core::Plugin>) {
+		self.builtins.push(LoadedPlugin::builtin(identifier, plugin));
+	}
+
 	/**
 	 * Attempts to load a plugin at the specified path.
 	 * Returns a result indicating success.
@@ -22,8 +99,10 @@ impl PluginParser {
 
 	pub fn load<P: AsRef<std::ffi::OsStr>>(&mut self, app: &Shared<App>, library_path: P)
 		-> std::io::Result<()> {
+		self.paths.push(library_path.as_ref().to_owned());
+
 		unsafe {
-			let library = Rc::new(libloading::Library::new(library_path)
+			let library = Arc::new(libloading::Library::new(library_path)
 				.map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", err)))?);
 
 			let decl = library.get::<*mut scout_core::PluginDeclaration>(b"PLUGIN_DECLARATION\0")
@@ -33,21 +112,21 @@ impl PluginParser {
 				return Err(std::io::Error::new(std::io::ErrorKind::Other, "Plugin Version Mismatch.")); }
 
 			let bindings: Shared<Box<dyn scout_core::PluginBindings>> =
-				Shared::new(Box::new(PluginBindings::new(app.clone(), Rc::clone(&library))));
+				Shared::new(Box::new(PluginBindings::new(app.clone(), Arc::clone(&library))));
 
 			(decl.register)(bindings.clone());
 
 			let bindings_borrow = bindings.borrow();
 			match bindings_borrow.get_plugin() {
 				Ok((identifier, plugin)) => {
-					println!("Registered plugin '{}'.", identifier);
-					self.plugins.push(plugin);
+					log::info!("Registered plugin '{}'.", identifier);
+					self.plugins.push(LoadedPlugin { identifier: identifier.to_owned(), plugin, _lib: Some(library.clone()) });
 					drop(bindings_borrow);
 					self._bindings.push(bindings);
 					Ok(())
 				},
 				Err(err) => {
-					println!("Failed to register plugin: {:?}", err);
+					log::error!("Failed to register plugin: {:?}", err);
 					Err(std::io::Error::new(std::io::ErrorKind::Other, "Failed to register"))
 				}
 			}
@@ -55,23 +134,97 @@ impl PluginParser {
 	}
 
 	/**
-	 * Calls a plugin by name,
-	 * Returns a result with data or an error.
+	 * Unloads all plugins, and loads them again from the paths they were originally loaded from.
+	 * Plugins that fail to load are skipped. Any results from the old plugins keep their libraries loaded until dropped.
 	 */
 
-	pub fn get_results(&self, query: &str) -> Vec<Box<dyn SearchResult>> {
-		let mut results = vec![];
+	pub fn reload(&mut self, app: &Shared<App>) {
+		let paths = std::mem::replace(&mut self.paths, vec![]);
+		self.plugins.clear();
+		self._bindings.clear();
 
-		for plugin in self.plugins.iter() {
-			let res = scout_core::or_continue!(plugin.borrow().get_results(query));
-			results.extend(res);
+		for path in paths {
+			if let Err(err) = self.load(app, &path) { log::error!("Failed to reload plugin {:?}: {}", path, err); }
 		}
+	}
+
+	/**
+	 * Returns handles to all loaded plugins, which can be queried from other threads.
+	 */
+
+	pub fn get_plugins(&self) -> Vec<LoadedPlugin> {
+		self.builtins.iter().chain(self.plugins.iter()).cloned().collect()
+	}
+
+	/**
+	 * Queries the plugins provided, returning their results ranked with the default configuration.
+	 * This is safe to call from a worker thread.
+	 */
+
+	pub fn get_results(plugins: &[LoadedPlugin], query: &str, boosts: &Boosts) -> Vec<PluginResult> {
+		PluginParser::get_results_with(plugins, query, boosts, &RankingConfig::default())
+	}
+
+	/**
+	 * Queries the plugins provided, returning their ranked results.
+	 * Each plugin's query time is recorded in the profiler.
+	 */
+
+	pub fn get_results_with(plugins: &[LoadedPlugin], query: &str, boosts: &Boosts, config: &RankingConfig) -> Vec<PluginResult> {
+		let results = plugins.iter().flat_map(|plugin| PluginParser::get_plugin_results(plugin, query)).collect();
+		PluginParser::rank(results, query, boosts, config)
+	}
+
+	/**
+	 * Queries a single plugin, returning its results unranked, or none if the query fails.
+	 * The query time is recorded in the profiler.
+	 */
 
-		results.retain(|result| result.get_score() > 0);
-		results.sort_by(|result_a, result_b| result_b.get_score().partial_cmp(&result_a.get_score()).unwrap());
-		let min = if results.len() >= 1 { (results[0].get_score() as f64 * 0.75) as usize } else { 0 };
-		results.retain(|result| result.get_score() >= min);
+	pub fn get_plugin_results(plugin: &LoadedPlugin, query: &str) -> Vec<PluginResult> {
+		let start = std::time::Instant::now();
+		let res = plugin.plugin.get_results(query);
+		let count = res.as_ref().map(|res| res.len()).unwrap_or(0);
+		profiler::record_query(&plugin.identifier, start.elapsed(), count);
+
+		res.map(|res| res.into_iter().map(|result| plugin.wrap(result)).collect()).unwrap_or_default()
+	}
+
+	/**
+	 * Ranks results by their score plus their weighted history boost,
+	 * dropping results scoring under the cutoff fraction of the top result.
+	 * How every candidate was ranked is recorded in the profiler.
+	 */
 
+	pub fn rank(mut results: Vec<PluginResult>, query: &str, boosts: &Boosts, config: &RankingConfig) -> Vec<PluginResult> {
+		results.retain(|res| res.result.get_score() > 0);
+		let mut results = results.into_iter().map(|res| {
+			let boost = (boosts.get(&res.plugin, &res.result.get_id()) as f64 * config.history_weight).round() as usize;
+			(boost, res)
+		}).collect::<Vec<_>>();
+
+		results.sort_by(|(boost_a, res_a), (boost_b, res_b)|
+			(res_b.result.get_score() + boost_b).cmp(&(res_a.result.get_score() + boost_a)));
+		let top = results.first().map(|(boost, res)| res.result.get_score() + boost).unwrap_or(0);
+		let min = (top as f64 * config.cutoff) as usize;
+
+		let mut candidates = vec![];
+		let results = results.into_iter().enumerate().filter_map(|(position, (boost, res))| {
+			let score = res.result.get_score();
+			let kept = score + boost >= min;
+
+			candidates.push(profiler::Candidate {
+				plugin: res.plugin.clone(),
+				title: res.result.get_title(),
+				score,
+				boost,
+				normalized: if top > 0 { (score + boost) as f64 / top as f64 } else { 0.0 },
+				position: if kept { Some(position) } else { None }
+			});
+
+			if kept { Some(res) } else { None }
+		}).collect();
+
+		profiler::record_ranking(query, candidates);
 		results
 	}
 }
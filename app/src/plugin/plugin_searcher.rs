@@ -0,0 +1,104 @@
+/**! Queries plugins on a worker thread, debouncing input and discarding stale results. */
+
+use std::sync::{ Arc, mpsc };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+
+use scout_core::Shared;
+
+use crate::history::Boosts;
+use super::plugin_parser::{ PluginParser, PluginResult, LoadedPlugin };
+
+/** How long to wait after the last input before querying plugins, in milliseconds. */
+static DEBOUNCE_MS: u32 = 60;
+
+/** A query waiting to be run on the worker thread. */
+struct Query {
+	generation: usize,
+	query: String,
+	plugins: Vec<LoadedPlugin>,
+	boosts: Boosts,
+
+	/** Whether this refreshes a single plugin's results, which are returned unranked. */
+	refresh: bool
+}
+
+pub struct PluginSearcher {
+	generation: Arc<AtomicUsize>,
+	pending: Shared<Option<glib::SourceId>>,
+	queries: mpsc::Sender<Query>
+}
+
+impl PluginSearcher {
+
+	/**
+	 * Spawns the search worker thread.
+	 * `on_results` is called on the main thread with the results of the latest query,
+	 * and `on_update` is called with a plugin's refreshed results and the query they are for.
+	 * Results for queries that have since been superseded are dropped.
+	 */
+
+	pub fn new<F, U>(mut on_results: F, mut on_update: U) -> Self where
+		F: FnMut(Vec<PluginResult>) + 'static, U: FnMut(&str, &str, Vec<PluginResult>) + 'static {
+		let generation = Arc::new(AtomicUsize::new(0));
+		let (queries, queries_rx) = mpsc::channel::<Query>();
+		let (results, results_rx) = glib::MainContext::channel::<(usize, Option<(String, String)>, Vec<PluginResult>)>(glib::PRIORITY_DEFAULT);
+
+		let generation_clone = generation.clone();
+		std::thread::Builder::new().name("scout-search".to_owned()).spawn(move || {
+			while let Ok(query) = queries_rx.recv() {
+				if query.generation != generation_clone.load(Ordering::SeqCst) { continue; }
+
+				let sent = match (query.refresh, query.plugins.first()) {
+					(true, Some(plugin)) => results.send((query.generation,
+						Some((plugin.identifier.clone(), query.query.clone())), PluginParser::get_plugin_results(plugin, &query.query))),
+					_ => results.send((query.generation, None, PluginParser::get_results(&query.plugins, &query.query, &query.boosts)))
+				};
+
+				if sent.is_err() { break; }
+			}
+		}).expect("Failed to spawn the search thread.");
+
+		let generation_clone = generation.clone();
+		results_rx.attach(None, move |(generation, refreshed, results)| {
+			if generation == generation_clone.load(Ordering::SeqCst) {
+				match refreshed {
+					Some((plugin, query)) => on_update(&plugin, &query, results),
+					None => on_results(results)
+				}
+			}
+			glib::Continue(true)
+		});
+
+		PluginSearcher { generation, pending: Shared::new(None), queries }
+	}
+
+	/**
+	 * Schedules a query to be run once input settles, ranking previously used results higher by their boosts.
+	 * Any query that hasn't returned yet is invalidated.
+	 */
+
+	pub fn search(&mut self, query: &str, plugins: Vec<LoadedPlugin>, boosts: Boosts) {
+		let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+		if let Some(source) = self.pending.replace(None) { glib::source_remove(source); }
+
+		let queries = self.queries.clone();
+		let pending_clone = self.pending.clone();
+		let mut query = Some(Query { generation, query: query.to_owned(), plugins, boosts, refresh: false });
+
+		self.pending.replace(Some(glib::timeout_add_local(DEBOUNCE_MS, move || {
+			pending_clone.replace(None);
+			if let Some(query) = query.take() { drop(queries.send(query)); }
+			glib::Continue(false)
+		})));
+	}
+
+	/**
+	 * Queries a single plugin again for the current query, without waiting for input to settle.
+	 * The refreshed results are dropped if a new query is made before they are returned.
+	 */
+
+	pub fn refresh(&mut self, query: &str, plugin: LoadedPlugin) {
+		let generation = self.generation.load(Ordering::SeqCst);
+		drop(self.queries.send(Query { generation, query: query.to_owned(), plugins: vec![ plugin ], boosts: Boosts::default(), refresh: true }));
+	}
+}
@@ -0,0 +1,246 @@
+/**! Runs plugins' background tasks on a pool of worker threads, and their main thread tasks and timers on the main loop. */
+
+use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::{ Arc, Weak, Mutex, Condvar, PoisonError, mpsc };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+
+use scout_core::{ Shared, TaskRunner, TimerId };
+
+/** How many worker threads run background tasks. */
+static POOL_SIZE: usize = 4;
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/**
+ * A background task, along with the plugin that queued it.
+ * The plugin's library is kept loaded until any panic from the task has been logged and dropped,
+ * as the panic's message may be stored in the library.
+ */
+
+struct Job {
+	plugin: String,
+	task: Task,
+	library: Option<Arc<libloading::Library>>
+}
+
+/** A request to the main thread. */
+enum MainMessage {
+	Run(Task),
+	Schedule(TimerId, Duration, Box<dyn FnMut() -> bool + Send>),
+	Cancel(TimerId),
+	CancelAll
+}
+
+pub struct TaskManager {
+	jobs: Mutex<mpsc::Sender<Job>>,
+	main: Mutex<glib::Sender<MainMessage>>,
+	next_timer: AtomicUsize,
+
+	/** How many background jobs are queued or running, which `flush` waits on. */
+	pending: Arc<(Mutex<usize>, Condvar)>
+}
+
+impl TaskManager {
+
+	/**
+	 * Spawns the worker threads, and starts receiving main thread tasks.
+	 * This must be called on the main thread.
+	 */
+
+	pub fn new() -> Arc<Self> {
+		let (jobs, jobs_rx) = mpsc::channel::<Job>();
+		let jobs_rx = Arc::new(Mutex::new(jobs_rx));
+		let pending = Arc::new((Mutex::new(0), Condvar::new()));
+
+		for i in 0..POOL_SIZE {
+			let jobs_rx = jobs_rx.clone();
+			let pending = pending.clone();
+			std::thread::Builder::new().name(format!("scout-task-{}", i)).spawn(move || loop {
+				let Job { plugin, task, library } = match jobs_rx.lock().unwrap().recv() { Ok(job) => job, Err(_) => break };
+				let _pending = PendingGuard(&pending);
+
+				if let Err(panic) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(task)) {
+					let message = panic.downcast_ref::<&str>().map(|message| message.to_string())
+						.or_else(|| panic.downcast_ref::<String>().cloned()).unwrap_or_default();
+					log::error!("A background task from plugin '{}' panicked: {}", plugin, message);
+					drop(panic);
+				}
+
+				drop(library);
+			}).expect("Failed to spawn a task thread.");
+		}
+
+		let (main, main_rx) = glib::MainContext::channel::<MainMessage>(glib::PRIORITY_DEFAULT_IDLE);
+		let timers: Shared<HashMap<TimerId, glib::SourceId>> = Shared::new(HashMap::new());
+
+		main_rx.attach(None, move |message| {
+			match message {
+				MainMessage::Run(task) => task(),
+				MainMessage::Schedule(id, interval, mut task) => {
+					let timers_clone = timers.clone();
+					let source = glib::timeout_add_local(interval.as_millis() as u32, move || {
+						if task() { return glib::Continue(true); }
+						timers_clone.borrow_mut().remove(&id);
+						glib::Continue(false)
+					});
+					timers.borrow_mut().insert(id, source);
+				},
+				MainMessage::Cancel(id) => if let Some(source) = timers.borrow_mut().remove(&id) { glib::source_remove(source); },
+				MainMessage::CancelAll => timers.borrow_mut().drain().for_each(|(_, source)| glib::source_remove(source))
+			}
+			glib::Continue(true)
+		});
+
+		Arc::new(TaskManager { jobs: Mutex::new(jobs), main: Mutex::new(main), next_timer: AtomicUsize::new(0), pending })
+	}
+
+	/**
+	 * Returns a task runner for a plugin. Tasks keep the plugin's library loaded until they finish,
+	 * and tasks started after the library has been unloaded are dropped.
+	 * Panics in the plugin's background tasks are logged with its identifier.
+	 */
+
+	pub fn get_runner(self: &Arc<Self>, plugin: &str, library: Option<&Arc<libloading::Library>>) -> Arc<dyn TaskRunner> {
+		Arc::new(PluginRunner { manager: self.clone(), plugin: plugin.to_owned(), library: library.map(Arc::downgrade) })
+	}
+
+	/**
+	 * Cancels every timer. This is called before plugins are reloaded, as the old plugins' timers would keep them loaded.
+	 */
+
+	pub fn cancel_all(&self) {
+		self.main.lock().unwrap().send(MainMessage::CancelAll).ok();
+	}
+
+	/**
+	 * Waits for queued background tasks to finish, and runs the main thread tasks they queue.
+	 * This is used when running without a main loop, so that plugins finish loading before they are queried.
+	 */
+
+	pub fn flush(&self) {
+		let context = glib::MainContext::default();
+		loop {
+			let (count, done) = &*self.pending;
+			let mut pending = count.lock().unwrap();
+			while *pending > 0 { pending = done.wait(pending).unwrap(); }
+			drop(pending);
+
+			if !context.pending() { break; }
+			while context.iteration(false) {}
+		}
+	}
+
+	fn spawn(&self, job: Job) {
+		*self.pending.0.lock().unwrap() += 1;
+		if self.jobs.lock().unwrap().send(job).is_err() { *self.pending.0.lock().unwrap() -= 1; }
+	}
+
+	fn send_main(&self, message: MainMessage) {
+		self.main.lock().unwrap().send(message).ok();
+	}
+}
+
+/**
+ * Decrements the count of pending background jobs when dropped,
+ * so that `flush` doesn't wait forever if a worker thread unwinds.
+ */
+
+struct PendingGuard<'a>(&'a (Mutex<usize>, Condvar));
+
+impl <'a> Drop for PendingGuard<'a> {
+	fn drop(&mut self) {
+		let (count, done) = self.0;
+		*count.lock().unwrap_or_else(PoisonError::into_inner) -= 1;
+		done.notify_all();
+	}
+}
+
+/**
+ * Runs tasks for a single plugin.
+ */
+
+struct PluginRunner {
+	manager: Arc<TaskManager>,
+	plugin: String,
+	library: Option<Weak<libloading::Library>>
+}
+
+impl PluginRunner {
+
+	/**
+	 * Returns a handle that keeps the plugin's library loaded, or None if it has been unloaded.
+	 * Built in plugins have no library, so always return a handle.
+	 */
+
+	fn keep_loaded(&self) -> Option<Option<Arc<libloading::Library>>> {
+		match &self.library {
+			Some(library) => library.upgrade().map(Some),
+			None => Some(None)
+		}
+	}
+}
+
+impl TaskRunner for PluginRunner {
+	fn spawn(&self, task: Task) {
+		if let Some(library) = self.keep_loaded() {
+			self.manager.spawn(Job { plugin: self.plugin.clone(), task, library });
+		}
+	}
+
+	fn run_on_main(&self, task: Task) {
+		if let Some(library) = self.keep_loaded() {
+			self.manager.send_main(MainMessage::Run(Box::new(move || { task(); drop(library); })));
+		}
+	}
+
+	fn schedule(&self, interval: Duration, task: Box<dyn FnMut() -> bool + Send>) -> TimerId {
+		let id = TimerId(self.manager.next_timer.fetch_add(1, Ordering::SeqCst));
+		if let Some(library) = self.keep_loaded() {
+			// Tuple fields are dropped in order, so the task is dropped before its library.
+			let mut held = (task, library);
+			self.manager.send_main(MainMessage::Schedule(id, interval, Box::new(move || (held.0)())));
+		}
+		id
+	}
+
+	fn cancel(&self, timer: TimerId) {
+		self.manager.send_main(MainMessage::Cancel(timer));
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::atomic::{ AtomicUsize, Ordering };
+
+	use scout_core::Tasks;
+	use super::{ TaskManager, POOL_SIZE };
+
+	/**
+	 * A panicking task is logged and counted as finished, so `flush` returns, and the worker threads keep running tasks.
+	 * The test acquires the default main context, which main thread tasks are received on.
+	 */
+
+	#[test]
+	fn survives_panicking_tasks() {
+		glib::MainContext::default().with_thread_default(|| {
+			let manager = TaskManager::new();
+			let tasks = Tasks::new(manager.get_runner("test", None));
+
+			tasks.spawn(|| panic!("The task failed."));
+			manager.flush();
+			assert_eq!(*manager.pending.0.lock().unwrap(), 0);
+
+			let ran = Arc::new(AtomicUsize::new(0));
+			for _ in 0..POOL_SIZE * 2 {
+				let ran = ran.clone();
+				tasks.spawn(move || { ran.fetch_add(1, Ordering::SeqCst); });
+			}
+
+			manager.flush();
+			assert_eq!(ran.load(Ordering::SeqCst), POOL_SIZE * 2);
+		});
+	}
+}
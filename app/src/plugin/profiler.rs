@@ -0,0 +1,165 @@
+/**! Records how long plugins take to return results and create widgets, and how results were ranked, for the Developer preferences page. */
+
+use std::time::Duration;
+use std::sync::Mutex;
+use std::collections::{ HashMap, VecDeque };
+use once_cell::sync::Lazy;
+
+/** How many recent samples are kept for each plugin. */
+static MAX_SAMPLES: usize = 100;
+
+/**
+ * Timings for a single plugin, most recent last.
+ */
+
+#[derive(Debug, Default)]
+struct Samples {
+	queries: VecDeque<(Duration, usize)>,
+	widgets: VecDeque<Duration>
+}
+
+/**
+ * A summary of a plugin's recent timings.
+ */
+
+#[derive(Debug, Clone)]
+pub struct PluginStats {
+	pub identifier: String,
+
+	/** How many queries were timed. */
+	pub queries: usize,
+	pub last_query: Duration,
+	pub p95_query: Duration,
+
+	/** How many results the last query returned. */
+	pub last_count: usize,
+
+	/** The 95th percentile time to create a result's widgets, if any have been created. */
+	pub p95_widget: Option<Duration>
+}
+
+/**
+ * A result that was returned for a query, and how it was ranked.
+ */
+
+#[derive(Debug, Clone)]
+pub struct Candidate {
+	pub plugin: String,
+	pub title: String,
+
+	/** The score the plugin gave the result. */
+	pub score: usize,
+
+	/** The score added for previous uses of the result. */
+	pub boost: usize,
+
+	/** The result's final score as a fraction of the top result's. Results under the cutoff, 0.75 by default, are dropped. */
+	pub normalized: f64,
+
+	/** The result's position in the results, or `None` if it was dropped. */
+	pub position: Option<usize>
+}
+
+/**
+ * How the results for the latest query were ranked.
+ */
+
+#[derive(Debug, Clone, Default)]
+pub struct Ranking {
+	pub query: String,
+	pub candidates: Vec<Candidate>,
+
+	/** Increases for every query, so views can tell when the ranking has changed. */
+	pub generation: usize
+}
+
+static SAMPLES: Lazy<Mutex<HashMap<String, Samples>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+static RANKING: Lazy<Mutex<Ranking>> = Lazy::new(|| Mutex::new(Ranking::default()));
+
+fn push<T>(samples: &mut VecDeque<T>, sample: T) {
+	if samples.len() >= MAX_SAMPLES { samples.pop_front(); }
+	samples.push_back(sample);
+}
+
+/**
+ * Returns the 95th percentile of a set of durations.
+ */
+
+fn p95<I: Iterator<Item = Duration>>(durations: I) -> Option<Duration> {
+	let mut durations = durations.collect::<Vec<_>>();
+	if durations.is_empty() { return None; }
+	durations.sort();
+	let index = ((durations.len() as f64 * 0.95).ceil() as usize).max(1) - 1;
+	Some(durations[index])
+}
+
+/**
+ * Records how long a plugin's `get_results` took, and how many results it returned.
+ * This is safe to call from a worker thread.
+ */
+
+pub fn record_query(identifier: &str, duration: Duration, count: usize) {
+	let mut samples = SAMPLES.lock().unwrap();
+	push(&mut samples.entry(identifier.to_owned()).or_default().queries, (duration, count));
+}
+
+/**
+ * Records how long it took to create a result's widget.
+ */
+
+pub fn record_widget(identifier: &str, duration: Duration) {
+	let mut samples = SAMPLES.lock().unwrap();
+	push(&mut samples.entry(identifier.to_owned()).or_default().widgets, duration);
+}
+
+/**
+ * Times a function, recording it as widget creation for a plugin.
+ */
+
+pub fn time_widget<T, F: FnOnce() -> T>(identifier: &str, create: F) -> T {
+	let start = std::time::Instant::now();
+	let widget = create();
+	record_widget(identifier, start.elapsed());
+	widget
+}
+
+/**
+ * Returns a summary of each plugin's recent timings, slowest first.
+ */
+
+pub fn get_stats() -> Vec<PluginStats> {
+	let samples = SAMPLES.lock().unwrap();
+	let mut stats = samples.iter().filter_map(|(identifier, samples)| {
+		let (last_query, last_count) = *samples.queries.back()?;
+		Some(PluginStats {
+			identifier: identifier.clone(),
+			queries: samples.queries.len(),
+			last_query,
+			p95_query: p95(samples.queries.iter().map(|(duration, _)| *duration)).unwrap_or_default(),
+			last_count,
+			p95_widget: p95(samples.widgets.iter().cloned())
+		})
+	}).collect::<Vec<_>>();
+
+	stats.sort_by(|a, b| b.p95_query.cmp(&a.p95_query));
+	stats
+}
+
+/**
+ * Records how the results for a query were ranked, replacing the previous ranking.
+ */
+
+pub fn record_ranking(query: &str, candidates: Vec<Candidate>) {
+	let mut ranking = RANKING.lock().unwrap();
+	let generation = ranking.generation + 1;
+	*ranking = Ranking { query: query.to_owned(), candidates, generation };
+}
+
+/**
+ * Returns how the results for the latest query were ranked.
+ */
+
+pub fn get_ranking() -> Ranking {
+	RANKING.lock().unwrap().clone()
+}
@@ -0,0 +1,211 @@
+/**! A built-in plugin, which provides session actions and the app's own actions as search results. */
+
+use gio::prelude::*;
+use gtk::prelude::*;
+
+use scout_core::{ Plugin, SearchResult };
+use crate::session;
+
+/**
+ * An action that can be searched for, which activates one of the window's GIO actions.
+ */
+
+#[derive(Debug, Clone)]
+struct Entry {
+	title: String,
+	subtitle: &'static str,
+	icon: &'static str,
+	action: String,
+	keywords: &'static [&'static str]
+}
+
+pub struct SystemPlugin {
+	entries: Vec<Entry>
+}
+
+impl SystemPlugin {
+	pub fn new() -> Self {
+		let mut entries = session::Action::ALL.iter().map(|action| Entry {
+			title: action.get_label().to_owned(),
+			subtitle: "System",
+			icon: action.get_icon(),
+			action: format!("system.{}", action.get_name()),
+			keywords: match action {
+				session::Action::Lock => &[ "lock" ],
+				session::Action::Logout => &[ "logout", "log off", "sign out" ],
+				session::Action::Suspend => &[ "sleep" ],
+				session::Action::Hibernate => &[],
+				session::Action::Shutdown => &[ "shutdown", "power off", "turn off" ],
+				session::Action::Restart => &[ "reboot" ]
+			}
+		}).collect::<Vec<_>>();
+
+		entries.push(Entry { title: "Preferences".to_owned(), subtitle: "Scout", icon: "preferences-system-symbolic",
+			action: "app.preferences".to_owned(), keywords: &[ "settings", "options", "configure" ] });
+		entries.push(Entry { title: "About Scout".to_owned(), subtitle: "Scout", icon: "dialog-information-symbolic",
+			action: "app.about".to_owned(), keywords: &[ "version" ] });
+
+		SystemPlugin { entries }
+	}
+
+	/**
+	 * Scores how well a query matches a name, by how closely its letters appear in order.
+	 * This matches the scoring used by the application plugin, so results rank comparably.
+	 */
+
+	pub(crate) fn score(name: &str, query: &str) -> usize {
+		let mut score = 0;
+		let mut last_letter_ind: usize = 0;
+		let mut name = name.to_lowercase();
+		name.retain(|c| !c.is_whitespace());
+
+		for letter in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+			let mut name_chars = name.chars().skip(last_letter_ind);
+			let pos = name_chars.position(|c| c == letter).map_or(-1, |c| c as isize);
+			if pos >= 0 {
+				last_letter_ind += pos as usize + 1;
+				score += std::cmp::max(10 - pos, 0) as usize
+			}
+		}
+
+		score
+	}
+}
+
+impl Plugin for SystemPlugin {
+	fn get_results(&self, query: &str) -> scout_core::Result<Vec<Box<dyn SearchResult>>> {
+		Ok(self.entries.iter().map(|entry| {
+			let score = std::iter::once(entry.title.as_str()).chain(entry.keywords.iter().cloned())
+				.map(|name| SystemPlugin::score(name, query)).max().unwrap_or(0);
+			Box::new(SystemResult { entry: entry.clone(), score }) as Box<dyn SearchResult>
+		}).filter(|result| result.get_score() > 0).collect())
+	}
+}
+
+
+/**
+ * A search result for a system action.
+ */
+
+pub struct SystemResult {
+	entry: Entry,
+	score: usize
+}
+
+impl SystemResult {
+
+	/**
+	 * Activates a GIO action on the main window, like `system.shutdown`.
+	 * This is deferred until the main loop is idle, as the window may be borrowed while results are activated.
+	 */
+
+	fn activate_action(action: &str) {
+		let action = action.to_owned();
+		glib::idle_add_local(move || {
+			let mut parts = action.splitn(2, '.');
+			let (group, name) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+
+			let window = gio::Application::get_default()
+				.and_then(|app| app.downcast::<gtk::Application>().ok())
+				.and_then(|app| app.get_windows().into_iter().find(|window| window.is::<gtk::ApplicationWindow>()));
+
+			match window.and_then(|window| window.get_action_group(group)) {
+				Some(group) => group.activate_action(name, None),
+				None => log::warn!("Action '{}' isn't available.", action)
+			}
+
+			glib::Continue(false)
+		});
+	}
+}
+
+impl SearchResult for SystemResult {
+	fn get_score(&self) -> usize {
+		self.score
+	}
+
+	fn get_id(&self) -> String {
+		self.entry.action.clone()
+	}
+
+	fn get_title(&self) -> String {
+		self.entry.title.clone()
+	}
+
+	fn get_subtitle(&self) -> String {
+		self.entry.subtitle.to_owned()
+	}
+
+	fn get_actions(&self) -> Vec<String> {
+		vec![]
+	}
+
+	fn set_first(&self, widget: &gtk::Widget, first: bool) -> () {
+		let widget = widget.downcast_ref::<gtk::Box>().unwrap();
+		widget.get_children()[0].set_can_focus(!first);
+	}
+
+	fn activate(&self) {
+		SystemResult::activate_action(&self.entry.action);
+	}
+
+	fn get_result_widget(&self) -> gtk::Widget {
+		let widget = gtk::Box::new(gtk::Orientation::Vertical, 0);
+		widget.get_style_context().add_class("System");
+		widget.set_widget_name("SearchResult");
+
+		let button = gtk::Button::new();
+		button.get_style_context().add_class("flat");
+		widget.pack_start(&button, true, true, 0);
+
+		let action = self.entry.action.clone();
+		button.connect_clicked(move |_| SystemResult::activate_action(&action));
+
+		let widget_top = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+		button.add(&widget_top);
+
+		let icon = gtk::Image::from_icon_name(Some(self.entry.icon), gtk::IconSize::LargeToolbar);
+		icon.set_size_request(32, 32);
+		widget_top.pack_start(&icon, false, false, 4);
+
+		let description_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+		widget_top.pack_start(&description_box, true, true, 0);
+
+		let category_label = gtk::Label::new(Some(&[ "<span size='small' weight='bold'>",
+			&self.entry.subtitle.to_uppercase(), "</span>" ].join("")));
+		category_label.set_use_markup(true);
+		category_label.set_xalign(0.0);
+		description_box.pack_start(&category_label, false, false, 1);
+
+		let label = gtk::Label::new(Some(&self.entry.title));
+		label.set_ellipsize(pango::EllipsizeMode::End);
+		label.set_xalign(0.0);
+		description_box.pack_start(&label, false, false, 1);
+
+		widget.upcast()
+	}
+
+	fn get_preview_widget(&self) -> gtk::Widget {
+		let widget = gtk::Box::new(gtk::Orientation::Vertical, 4);
+		widget.get_style_context().add_class("System");
+		widget.set_widget_name("SearchPreview");
+		widget.set_border_width(36);
+
+		let icon = gtk::Image::from_icon_name(Some(self.entry.icon), gtk::IconSize::Dialog);
+		icon.set_pixel_size(96);
+		widget.pack_start(&icon, false, false, 0);
+
+		let label = gtk::Label::new(Some(&self.entry.title));
+		widget.pack_start(&label, false, false, 4);
+
+		let activate_button = gtk::Button::with_label(&self.entry.title);
+		activate_button.set_halign(gtk::Align::Center);
+		activate_button.set_valign(gtk::Align::End);
+		widget.pack_end(&activate_button, false, false, 0);
+
+		let action = self.entry.action.clone();
+		activate_button.connect_clicked(move |_| SystemResult::activate_action(&action));
+
+		widget.upcast()
+	}
+}
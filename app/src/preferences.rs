@@ -1,7 +1,28 @@
+use gio::prelude::*;
 use serde::{ Serialize, Deserialize };
-use std::io::{ Read, Write, Seek, SeekFrom };
+use std::rc::Rc;
+use std::cell::Ref;
+use std::path::{ Path, PathBuf };
+use std::collections::HashMap;
 
 use scout_core::Shared;
+use crate::paths;
+use crate::session;
+
+/** The current version of the configuration schema. Bump this and add a migration when the schema changes. */
+pub static CONFIG_VERSION: u32 = 1;
+
+/**
+ * Migrations between configuration schema versions, applied to the raw JSON before it is parsed.
+ * The migration at index `n` upgrades a version `n` config to version `n + 1`.
+ */
+
+static MIGRATIONS: &[fn(&mut serde_json::Map<String, serde_json::Value>)] = &[
+	// 0 -> 1: Configs from before versioning was introduced don't need any changes.
+	|_| ()
+];
+
+fn default_version() -> u32 { CONFIG_VERSION }
 
 fn default_opacity() -> u32 { 90 }
 
@@ -11,11 +32,73 @@ fn default_always_on_top() -> bool { true }
 
 fn default_developer() -> bool { false }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+fn default_log_queries() -> bool { false }
+
+fn default_theme() -> Option<String> { None }
+
+fn default_width() -> i32 { 700 }
+
+fn default_height() -> i32 { 500 }
+
+fn default_results_width() -> i32 { 220 }
+
+/**
+ * Where the window is placed vertically on its monitor.
+ */
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+	Center,
+	TopThird
+}
+
+impl Default for Anchor {
+	fn default() -> Self { Anchor::Center }
+}
+
+/**
+ * How results are laid out in the window.
+ */
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Layout {
+
+	/** Results beside a preview of the selected result. */
+	Full,
+
+	/** A single column of results, which the window grows to fit. */
+	Compact
+}
+
+impl Default for Layout {
+	fn default() -> Self { Layout::Full }
+}
+
+/**
+ * Which monitor the window opens on.
+ */
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Monitor {
+	Primary,
+	Pointer
+}
+
+impl Default for Monitor {
+	fn default() -> Self { Monitor::Primary }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Preferences {
 
 	#[serde(skip_serializing, default)]
-	pub path: std::path::PathBuf,
+	pub path: PathBuf,
+
+	#[serde(default = "default_version")]
+	pub version: u32,
 
 	#[serde(default = "default_opacity")]
 	pub opacity: u32,
@@ -27,44 +110,413 @@ pub struct Preferences {
 	pub always_on_top: bool,
 
 	#[serde(default = "default_developer")]
-	pub developer: bool
+	pub developer: bool,
+
+	/** Whether searches and the results activated from them are logged locally, for `scout eval`. */
+	#[serde(default = "default_log_queries")]
+	pub log_queries: bool,
+
+	/** The file name of the user theme to load from the themes directory, or None for the default style. */
+	#[serde(default = "default_theme")]
+	pub theme: Option<String>,
+
+	#[serde(default = "default_width")]
+	pub width: i32,
+
+	#[serde(default = "default_height")]
+	pub height: i32,
+
+	#[serde(default = "default_results_width")]
+	pub results_width: i32,
+
+	#[serde(default)]
+	pub anchor: Anchor,
+
+	#[serde(default)]
+	pub monitor: Monitor,
+
+	#[serde(default)]
+	pub layout: Layout,
+
+	/** Commands to run for session actions instead of the desktop's defaults, e.g. `{ "lock": "i3lock" }`. */
+	#[serde(default)]
+	pub session_commands: HashMap<session::Action, String>
 }
 
 impl Preferences {
-	pub fn new(path: Option<&std::path::Path>) -> Shared<Self> {
-		let path = path
-			.and_then(|path| Some(path.to_owned()))
-			.unwrap_or_else(|| std::path::Path::new(&[ "/home/", &whoami::username(), "/.config/scout.conf" ].join(""))
-			.to_owned());
-
-		let mut file = std::fs::OpenOptions::new().read(true).write(true).create(true).open(&path).unwrap();
-		let mut contents = String::new();
-		drop(file.read_to_string(&mut contents));
-		drop(file);
-
-		let prefs = Shared::new(match serde_json::from_str::<Preferences>(&contents) {
-			Ok(json) => json,
+
+	/**
+	 * Returns the default configuration file path, `$XDG_CONFIG_HOME/scout/config.json`.
+	 */
+
+	pub fn default_path() -> PathBuf {
+		paths::config_dir().join("config.json")
+	}
+
+	/**
+	 * Returns the path configuration was stored at before it was moved into the XDG config directory.
+	 */
+
+	fn legacy_path() -> PathBuf {
+		paths::home_dir().join(".config/scout.conf")
+	}
+
+	/**
+	 * Loads preferences from the path provided, or the default path.
+	 * Missing files are created with the default preferences, and files that can't be parsed
+	 * are backed up beside the original before being replaced. Existing files are only rewritten
+	 * if they were migrated, and are left alone if they couldn't be read for any other reason.
+	 */
+
+	pub fn new(path: Option<&Path>) -> Self {
+		let custom_path = path.is_some();
+		let path = path.map(|path| path.to_owned()).unwrap_or_else(Preferences::default_path);
+
+		let mut source = path.clone();
+		if !custom_path && !path.exists() && Preferences::legacy_path().exists() {
+			source = Preferences::legacy_path();
+			log::info!("Migrating config file from {:?} to {:?}.", source, path);
+		}
+
+		let (mut prefs, save) = match std::fs::read_to_string(&source) {
+			Ok(contents) => match Preferences::parse_migrated(&contents) {
+				Ok((prefs, migrated)) => (prefs, migrated || source != path),
+				Err(err) => {
+					log::warn!("Error reading config file, resetting to default. {}", err);
+					Preferences::backup(&source);
+					(Preferences::default(), true)
+				}
+			},
+			Err(err) if err.kind() == std::io::ErrorKind::NotFound => (Preferences::default(), true),
+			Err(err) if err.kind() == std::io::ErrorKind::InvalidData => {
+				log::warn!("Config file isn't valid UTF-8, resetting to default. {}", err);
+				Preferences::backup(&source);
+				(Preferences::default(), true)
+			},
 			Err(err) => {
-				println!("Error reading config file, resetting to default. {:?}", err);
-				serde_json::from_str("{}").unwrap()
+				log::warn!("Error reading config file, using the defaults without saving them. {}", err);
+				(Preferences::default(), false)
 			}
-		});
+		};
 
-		let mut prefs_mut = prefs.borrow_mut();
-		prefs_mut.path = path;
-		prefs_mut.save().unwrap();
-		drop(prefs_mut);
+		prefs.path = path;
+		if save {
+			if let Err(err) = prefs.save() { log::error!("Failed to save config file {:?}: {}", prefs.path, err); }
+		}
 
 		prefs
 	}
 
+	/**
+	 * Parses preferences from a JSON string, migrating them from older schema versions if necessary.
+	 */
+
+	pub fn parse(contents: &str) -> serde_json::Result<Self> {
+		Preferences::parse_migrated(contents).map(|(prefs, _)| prefs)
+	}
+
+	/**
+	 * Parses preferences like `parse`, also returning whether they were migrated from an older schema version.
+	 */
+
+	fn parse_migrated(contents: &str) -> serde_json::Result<(Self, bool)> {
+		let mut value = serde_json::from_str::<serde_json::Value>(contents)?;
+		let mut migrated = false;
+
+		if let Some(map) = value.as_object_mut() {
+			let version = map.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+
+			if version > CONFIG_VERSION {
+				log::warn!("Config file version {} is newer than this version of Scout supports ({}).",
+					version, CONFIG_VERSION);
+			}
+
+			for migration in MIGRATIONS.iter().skip(version as usize) { migration(map); }
+			if version < CONFIG_VERSION {
+				map.insert("version".to_owned(), CONFIG_VERSION.into());
+				migrated = true;
+			}
+		}
+
+		serde_json::from_value(value).map(|prefs| (prefs, migrated))
+	}
+
+	/**
+	 * Moves a config file that couldn't be parsed aside, so that it isn't lost when the defaults are saved.
+	 */
+
+	fn backup(path: &Path) {
+		let time = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+		let mut backup_name = path.file_name().unwrap_or_default().to_owned();
+		backup_name.push(format!(".{}.bak", time));
+		let backup = path.with_file_name(backup_name);
+
+		match std::fs::rename(path, &backup) {
+			Ok(()) => log::info!("The invalid config file was backed up to {:?}.", backup),
+			Err(err) => log::error!("Failed to back up the invalid config file: {}", err)
+		}
+	}
+
+	/**
+	 * Writes the preferences to their path, atomically.
+	 */
+
+	pub fn save(&self) -> std::io::Result<()> {
+		let contents = serde_json::to_string_pretty(&self)
+			.map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+		paths::write_atomic(&self.path, contents.as_bytes())
+	}
+}
+
+impl Default for Preferences {
+	fn default() -> Self {
+		serde_json::from_str("{}").unwrap()
+	}
+}
+
+/**
+ * Identifies a listener added with `PreferencesStore::connect_reload`, so that it can be disconnected.
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListenerId(usize);
+
+type Listener = Rc<dyn Fn(&Preferences)>;
+
+/**
+ * Preferences shared throughout the app, which notify listeners whenever they are changed.
+ * This can be cloned cheaply, all clones refer to the same preferences.
+ */
+
+#[derive(Clone)]
+pub struct PreferencesStore {
+	prefs: Shared<Preferences>,
+	listeners: Shared<Vec<Listener>>,
+	reload_listeners: Shared<Vec<(ListenerId, Listener)>>,
+	next_listener: Shared<usize>,
+	monitor: Shared<Option<gio::FileMonitor>>
+}
+
+impl PreferencesStore {
+	pub fn new(prefs: Preferences) -> Self {
+		PreferencesStore { prefs: Shared::new(prefs), listeners: Shared::new(vec![]),
+			reload_listeners: Shared::new(vec![]), next_listener: Shared::new(0), monitor: Shared::new(None) }
+	}
+
+	/**
+	 * Borrows the current preferences.
+	 */
+
+	pub fn get(&self) -> Ref<Preferences> {
+		self.prefs.borrow()
+	}
+
+	/**
+	 * Modifies the preferences, and notifies listeners of the change.
+	 */
+
+	pub fn set<F: FnOnce(&mut Preferences)>(&self, update: F) {
+		update(&mut self.prefs.borrow_mut());
+		self.notify();
+	}
+
+	/**
+	 * Replaces the preferences, and notifies listeners of the change.
+	 */
+
+	pub fn replace(&self, prefs: Preferences) {
+		self.prefs.replace(prefs);
+		self.notify();
+	}
+
+	/**
+	 * Adds a listener, which is called with the new preferences whenever they change.
+	 */
+
+	pub fn connect<F: Fn(&Preferences) + 'static>(&self, listener: F) {
+		self.listeners.borrow_mut().push(Rc::new(listener));
+	}
+
+	/**
+	 * Adds a listener, which is called with the new preferences after they are reloaded from the config file,
+	 * because it was changed by another program. Returns an identifier to disconnect the listener with.
+	 */
+
+	pub fn connect_reload<F: Fn(&Preferences) + 'static>(&self, listener: F) -> ListenerId {
+		let id = *self.next_listener.borrow();
+		self.next_listener.replace(id + 1);
+		self.reload_listeners.borrow_mut().push((ListenerId(id), Rc::new(listener)));
+		ListenerId(id)
+	}
+
+	/**
+	 * Removes a listener added with `connect_reload`.
+	 */
+
+	pub fn disconnect_reload(&self, id: ListenerId) {
+		self.reload_listeners.borrow_mut().retain(|(listener, _)| *listener != id);
+	}
+
+	/**
+	 * Writes the current preferences to disk.
+	 */
+
 	pub fn save(&self) -> std::io::Result<()> {
-		let mut file = std::fs::OpenOptions::new().read(true).write(true).create(true).open(&self.path)?;
+		self.prefs.borrow().save()
+	}
+
+	/**
+	 * Watches the config file, reloading the preferences when it is changed by another program.
+	 * If the new file can't be parsed, the current preferences are kept and `on_error` is called.
+	 */
+
+	pub fn watch<F: Fn(&Path, &serde_json::Error) + 'static>(&self, on_error: F) {
+		let path = self.get().path.clone();
+		let path = std::env::current_dir().map(|dir| dir.join(&path)).unwrap_or(path);
+		let dir = match path.parent() { Some(dir) => gio::File::new_for_path(dir), None => return };
+
+		// The directory is watched rather than the file, as atomic saves replace the file instead of changing it.
+		let monitor = match dir.monitor_directory(gio::FileMonitorFlags::NONE, None::<&gio::Cancellable>) {
+			Ok(monitor) => monitor,
+			Err(err) => {
+				log::warn!("Failed to watch config file {:?}: {}", path, err);
+				return;
+			}
+		};
+
+		let store = self.clone();
+		monitor.connect_changed(move |_, file, _, event| {
+			if file.get_path().as_deref() != Some(path.as_path()) { return; }
+			if event != gio::FileMonitorEvent::ChangesDoneHint && event != gio::FileMonitorEvent::Created { return; }
+
+			let contents = match std::fs::read_to_string(&path) { Ok(contents) => contents, Err(_) => return };
+			match Preferences::parse(&contents) {
+				Ok(mut prefs) => {
+					prefs.path = path.clone();
+					if prefs == *store.get() { return; }
+					store.replace(prefs.clone());
+
+					let listeners = store.reload_listeners.borrow().clone();
+					for (_, listener) in listeners.iter() { listener(&prefs); }
+				},
+				Err(err) => on_error(&path, &err)
+			}
+		});
+
+		self.monitor.replace(Some(monitor));
+	}
+
+	/**
+	 * Notifies listeners without changing the preferences,
+	 * so that files the preferences refer to, like themes, are reloaded.
+	 */
+
+	pub fn refresh(&self) {
+		self.notify();
+	}
+
+	fn notify(&self) {
+		let prefs = self.prefs.borrow().clone();
+		let listeners = self.listeners.borrow().clone();
+		for listener in listeners.iter() { listener(&prefs); }
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+	use super::{ Preferences, CONFIG_VERSION };
+
+	/**
+	 * Returns an empty temporary directory for a test's config files.
+	 */
+
+	fn temp_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("scout-preferences-test-{}-{}", std::process::id(), name));
+		drop(std::fs::remove_dir_all(&dir));
+		std::fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	fn get_backups(dir: &PathBuf) -> Vec<PathBuf> {
+		std::fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path())
+			.filter(|path| path.to_string_lossy().ends_with(".bak")).collect()
+	}
+
+	#[test]
+	fn migrates_unversioned_configs() {
+		let (prefs, migrated) = Preferences::parse_migrated(r#"{ "opacity": 50 }"#).unwrap();
+		assert!(migrated);
+		assert_eq!(prefs.version, CONFIG_VERSION);
+		assert_eq!(prefs.opacity, 50);
+
+		let (_, migrated) = Preferences::parse_migrated(&format!(r#"{{ "version": {} }}"#, CONFIG_VERSION)).unwrap();
+		assert!(!migrated);
+	}
+
+	#[test]
+	fn saves_migrated_configs() {
+		let dir = temp_dir("migrated");
+		let path = dir.join("config.json");
+		std::fs::write(&path, r#"{ "opacity": 50 }"#).unwrap();
+
+		let prefs = Preferences::new(Some(&path));
+		assert_eq!(prefs.opacity, 50);
+
+		let saved = serde_json::from_str::<serde_json::Value>(&std::fs::read_to_string(&path).unwrap()).unwrap();
+		assert_eq!(saved["version"], CONFIG_VERSION);
+		assert_eq!(saved["opacity"], 50);
+	}
+
+	#[test]
+	fn leaves_current_configs_unchanged() {
+		let dir = temp_dir("current");
+		let path = dir.join("config.json");
+		let contents = format!(r#"{{ "version": {}, "opacity": 50 }}"#, CONFIG_VERSION);
+		std::fs::write(&path, &contents).unwrap();
+
+		assert_eq!(Preferences::new(Some(&path)).opacity, 50);
+		assert_eq!(std::fs::read_to_string(&path).unwrap(), contents);
+	}
+
+	#[test]
+	fn backs_up_invalid_configs() {
+		let dir = temp_dir("invalid");
+		let path = dir.join("config.json");
+		std::fs::write(&path, "{ \"opacity\": ").unwrap();
+
+		let prefs = Preferences::new(Some(&path));
+		assert_eq!(prefs.opacity, Preferences::default().opacity);
+
+		let backups = get_backups(&dir);
+		assert_eq!(backups.len(), 1);
+		assert_eq!(std::fs::read_to_string(&backups[0]).unwrap(), "{ \"opacity\": ");
+		assert!(Preferences::parse(&std::fs::read_to_string(&path).unwrap()).is_ok());
+	}
+
+	#[test]
+	fn backs_up_configs_that_are_not_utf8() {
+		let dir = temp_dir("binary");
+		let path = dir.join("config.json");
+		std::fs::write(&path, [ 0xff, 0xfe, 0x00 ]).unwrap();
+
+		Preferences::new(Some(&path));
+		assert_eq!(get_backups(&dir).len(), 1);
+		assert!(Preferences::parse(&std::fs::read_to_string(&path).unwrap()).is_ok());
+	}
+
+	#[test]
+	fn creates_missing_configs() {
+		let dir = temp_dir("missing");
+		let path = dir.join("scout").join("config.json");
 
-		file.set_len(0)?;
-		file.seek(SeekFrom::Start(0))?;
-		file.write_all(serde_json::to_string(&self).unwrap().as_bytes())?;
+		let prefs = Preferences::new(Some(&path));
+		assert_eq!(prefs.path, path);
 
-		Ok(())
+		let mut saved = Preferences::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+		saved.path = path.clone();
+		assert_eq!(saved, prefs);
+		assert!(get_backups(&dir).is_empty());
 	}
 }
@@ -0,0 +1,70 @@
+/**! Logs searches and the results activated from them, so ranking changes can be evaluated offline with `scout eval`. */
+
+use serde::{ Serialize, Deserialize };
+use std::io::{ BufRead, Write };
+use std::path::{ Path, PathBuf };
+
+use crate::paths;
+
+/**
+ * Identifies a result by the plugin that provided it and its identifier.
+ */
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ResultKey {
+	pub plugin: String,
+	pub id: String
+}
+
+/**
+ * A search, the results that were shown for it in order, and the result that was activated.
+ */
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QueryRecord {
+
+	/** When the result was activated, in seconds since the Unix epoch. */
+	pub time: u64,
+	pub query: String,
+	pub shown: Vec<ResultKey>,
+	pub activated: ResultKey
+}
+
+/**
+ * Returns the default query log path, `$XDG_DATA_HOME/scout/queries.jsonl`.
+ */
+
+pub fn default_path() -> PathBuf {
+	paths::data_dir().join("queries.jsonl")
+}
+
+/**
+ * Appends a record to the log, as a line of JSON.
+ */
+
+pub fn append(path: &Path, record: &QueryRecord) -> std::io::Result<()> {
+	if let Some(parent) = path.parent() { std::fs::create_dir_all(parent)?; }
+	let line = serde_json::to_string(record).map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+	let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+	writeln!(file, "{}", line)
+}
+
+/**
+ * Reads every record from a log. Lines that can't be parsed are skipped.
+ */
+
+pub fn load(path: &Path) -> std::io::Result<Vec<QueryRecord>> {
+	let file = std::io::BufReader::new(std::fs::File::open(path)?);
+	let mut records = vec![];
+
+	for (i, line) in file.lines().enumerate() {
+		let line = line?;
+		if line.trim().is_empty() { continue; }
+		match serde_json::from_str::<QueryRecord>(&line) {
+			Ok(record) => records.push(record),
+			Err(err) => log::warn!("Skipping line {} of {:?}: {}", i + 1, path, err)
+		}
+	}
+
+	Ok(records)
+}
@@ -0,0 +1,324 @@
+/**! Session and power actions, like logging out and shutting down, run using the desktop's own tools where possible. */
+
+use serde::{ Serialize, Deserialize };
+use std::collections::HashMap;
+
+/**
+ * An action that affects the user's session or the computer's power state.
+ */
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+	Lock,
+	Logout,
+	Suspend,
+	Hibernate,
+	Shutdown,
+	Restart
+}
+
+impl Action {
+	pub const ALL: [Action; 6] = [ Action::Lock, Action::Logout, Action::Suspend,
+		Action::Hibernate, Action::Shutdown, Action::Restart ];
+
+	/**
+	 * Returns the action's identifier, which is used for its `system.*` action name and in the preferences.
+	 */
+
+	pub fn get_name(&self) -> &'static str {
+		match self {
+			Action::Lock => "lock",
+			Action::Logout => "logout",
+			Action::Suspend => "suspend",
+			Action::Hibernate => "hibernate",
+			Action::Shutdown => "shutdown",
+			Action::Restart => "restart"
+		}
+	}
+
+	pub fn get_label(&self) -> &'static str {
+		match self {
+			Action::Lock => "Lock Screen",
+			Action::Logout => "Log Out",
+			Action::Suspend => "Suspend",
+			Action::Hibernate => "Hibernate",
+			Action::Shutdown => "Shut Down",
+			Action::Restart => "Restart"
+		}
+	}
+
+	pub fn get_icon(&self) -> &'static str {
+		match self {
+			Action::Lock => "system-lock-screen-symbolic",
+			Action::Logout => "system-log-out-symbolic",
+			Action::Suspend => "media-playback-pause-symbolic",
+			Action::Hibernate => "document-save-symbolic",
+			Action::Shutdown => "system-shutdown-symbolic",
+			Action::Restart => "system-reboot-symbolic"
+		}
+	}
+
+	/**
+	 * Returns the progressive form of the action, for describing a pending action, e.g. "Shutting down".
+	 */
+
+	pub fn get_progressive(&self) -> &'static str {
+		match self {
+			Action::Lock => "Locking",
+			Action::Logout => "Logging out",
+			Action::Suspend => "Suspending",
+			Action::Hibernate => "Hibernating",
+			Action::Shutdown => "Shutting down",
+			Action::Restart => "Restarting"
+		}
+	}
+
+	/**
+	 * Returns whether the action ends the session, and should be confirmed before it is run.
+	 */
+
+	pub fn needs_confirmation(&self) -> bool {
+		match self {
+			Action::Logout | Action::Shutdown | Action::Restart => true,
+			_ => false
+		}
+	}
+}
+
+
+/**
+ * A desktop environment, which determines the commands used to run actions.
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Desktop {
+	Xfce,
+	Gnome,
+	Kde,
+
+	/** Any other desktop, which is controlled through systemd-logind. */
+	Logind
+}
+
+impl Desktop {
+
+	/**
+	 * Detects the current desktop from `XDG_CURRENT_DESKTOP`, falling back to logind.
+	 */
+
+	pub fn detect() -> Self {
+		Desktop::from_current_desktop(&std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default())
+	}
+
+	/**
+	 * Parses a colon-separated `XDG_CURRENT_DESKTOP` value.
+	 */
+
+	pub fn from_current_desktop(value: &str) -> Self {
+		for desktop in value.split(':').map(|desktop| desktop.to_lowercase()) {
+			match desktop.as_str() {
+				"xfce" => return Desktop::Xfce,
+				"gnome" | "gnome-classic" | "gnome-flashback" | "unity" => return Desktop::Gnome,
+				"kde" => return Desktop::Kde,
+				_ => ()
+			}
+		}
+
+		Desktop::Logind
+	}
+
+	/**
+	 * Returns the command used to run an action on this desktop.
+	 * The session ID is used by logind to end the session when logging out.
+	 */
+
+	pub fn get_command(&self, action: Action, session: &str) -> Vec<String> {
+		let command: Vec<&str> = match (self, action) {
+			(Desktop::Xfce, Action::Lock) => vec![ "xflock4" ],
+			(Desktop::Xfce, Action::Logout) => vec![ "xfce4-session-logout", "--logout" ],
+			(Desktop::Xfce, Action::Suspend) => vec![ "xfce4-session-logout", "--suspend" ],
+			(Desktop::Xfce, Action::Hibernate) => vec![ "xfce4-session-logout", "--hibernate" ],
+			(Desktop::Xfce, Action::Shutdown) => vec![ "xfce4-session-logout", "--halt" ],
+			(Desktop::Xfce, Action::Restart) => vec![ "xfce4-session-logout", "--reboot" ],
+
+			(Desktop::Gnome, Action::Logout) => vec![ "gnome-session-quit", "--logout", "--no-prompt" ],
+
+			(Desktop::Kde, Action::Logout) => vec![ "qdbus", "org.kde.ksmserver", "/KSMServer", "logout", "0", "0", "0" ],
+			(Desktop::Kde, Action::Shutdown) => vec![ "qdbus", "org.kde.ksmserver", "/KSMServer", "logout", "0", "2", "2" ],
+			(Desktop::Kde, Action::Restart) => vec![ "qdbus", "org.kde.ksmserver", "/KSMServer", "logout", "0", "1", "2" ],
+
+			(_, Action::Lock) => vec![ "loginctl", "lock-session" ],
+			(_, Action::Logout) => vec![ "loginctl", "terminate-session", session ],
+			(_, Action::Suspend) => vec![ "systemctl", "suspend" ],
+			(_, Action::Hibernate) => vec![ "systemctl", "hibernate" ],
+			(_, Action::Shutdown) => vec![ "systemctl", "poweroff" ],
+			(_, Action::Restart) => vec![ "systemctl", "reboot" ]
+		};
+
+		command.into_iter().map(|arg| arg.to_owned()).collect()
+	}
+}
+
+
+/**
+ * Runs commands for the session. This can be replaced to observe the commands that would be run.
+ */
+
+pub trait CommandRunner {
+	fn run(&self, command: &[String]) -> std::io::Result<()>;
+}
+
+/**
+ * Spawns commands as child processes, without waiting for them to exit.
+ */
+
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+	fn run(&self, command: &[String]) -> std::io::Result<()> {
+		let (program, args) = command.split_first()
+			.ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "Empty command."))?;
+		std::process::Command::new(program).args(args).spawn().map(|_| ())
+	}
+}
+
+
+/**
+ * Runs session actions, using user-configured commands if there are any, and the desktop's commands otherwise.
+ */
+
+pub struct Session {
+	desktop: Desktop,
+	session_id: String,
+	commands: HashMap<Action, String>,
+	runner: Box<dyn CommandRunner>
+}
+
+impl Session {
+
+	/**
+	 * Creates a session for the current desktop, using the custom commands provided.
+	 */
+
+	pub fn new(commands: &HashMap<Action, String>) -> Self {
+		let session_id = std::env::var("XDG_SESSION_ID").unwrap_or_default();
+		Session::with_runner(Desktop::detect(), &session_id, commands.clone(), Box::new(SystemRunner))
+	}
+
+	pub fn with_runner(desktop: Desktop, session_id: &str, commands: HashMap<Action, String>, runner: Box<dyn CommandRunner>) -> Self {
+		Session { desktop, session_id: session_id.to_owned(), commands, runner }
+	}
+
+	/**
+	 * Returns the command that will be run for an action.
+	 * Custom commands are run with `sh`, so they may contain arguments and pipes.
+	 */
+
+	pub fn get_command(&self, action: Action) -> Vec<String> {
+		match self.commands.get(&action).filter(|command| !command.trim().is_empty()) {
+			Some(command) => vec![ "sh".to_owned(), "-c".to_owned(), command.clone() ],
+			None => self.desktop.get_command(action, &self.session_id)
+		}
+	}
+
+	/**
+	 * Runs an action immediately.
+	 */
+
+	pub fn run(&self, action: Action) -> std::io::Result<()> {
+		let command = self.get_command(action);
+		log::info!("Executing '{}'", command.join(" "));
+		self.runner.run(&command)
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{ Arc, Mutex };
+	use std::collections::HashMap;
+	use super::{ Action, Desktop, Session, CommandRunner };
+
+	/**
+	 * Records the commands it is asked to run, instead of running them.
+	 */
+
+	#[derive(Clone, Default)]
+	struct RecordingRunner {
+		commands: Arc<Mutex<Vec<Vec<String>>>>
+	}
+
+	impl CommandRunner for RecordingRunner {
+		fn run(&self, command: &[String]) -> std::io::Result<()> {
+			self.commands.lock().unwrap().push(command.to_vec());
+			Ok(())
+		}
+	}
+
+	fn run(desktop: Desktop, commands: &[(Action, &str)], action: Action) -> Vec<String> {
+		let runner = RecordingRunner::default();
+		let commands = commands.iter().map(|(action, command)| (*action, command.to_string())).collect::<HashMap<_, _>>();
+		let session = Session::with_runner(desktop, "c2", commands, Box::new(runner.clone()));
+
+		session.run(action).unwrap();
+		let mut recorded = runner.commands.lock().unwrap();
+		assert_eq!(recorded.len(), 1);
+		recorded.remove(0)
+	}
+
+	#[test]
+	fn parses_current_desktop() {
+		assert_eq!(Desktop::from_current_desktop("XFCE"), Desktop::Xfce);
+		assert_eq!(Desktop::from_current_desktop("ubuntu:GNOME"), Desktop::Gnome);
+		assert_eq!(Desktop::from_current_desktop("KDE"), Desktop::Kde);
+		assert_eq!(Desktop::from_current_desktop("sway"), Desktop::Logind);
+		assert_eq!(Desktop::from_current_desktop(""), Desktop::Logind);
+	}
+
+	#[test]
+	fn uses_desktop_commands() {
+		let cases: &[(Desktop, Action, &[&str])] = &[
+			(Desktop::Xfce, Action::Lock, &[ "xflock4" ]),
+			(Desktop::Xfce, Action::Logout, &[ "xfce4-session-logout", "--logout" ]),
+			(Desktop::Xfce, Action::Suspend, &[ "xfce4-session-logout", "--suspend" ]),
+			(Desktop::Xfce, Action::Hibernate, &[ "xfce4-session-logout", "--hibernate" ]),
+			(Desktop::Xfce, Action::Shutdown, &[ "xfce4-session-logout", "--halt" ]),
+			(Desktop::Xfce, Action::Restart, &[ "xfce4-session-logout", "--reboot" ]),
+
+			(Desktop::Gnome, Action::Lock, &[ "loginctl", "lock-session" ]),
+			(Desktop::Gnome, Action::Logout, &[ "gnome-session-quit", "--logout", "--no-prompt" ]),
+			(Desktop::Gnome, Action::Shutdown, &[ "systemctl", "poweroff" ]),
+
+			(Desktop::Kde, Action::Logout, &[ "qdbus", "org.kde.ksmserver", "/KSMServer", "logout", "0", "0", "0" ]),
+			(Desktop::Kde, Action::Shutdown, &[ "qdbus", "org.kde.ksmserver", "/KSMServer", "logout", "0", "2", "2" ]),
+			(Desktop::Kde, Action::Restart, &[ "qdbus", "org.kde.ksmserver", "/KSMServer", "logout", "0", "1", "2" ]),
+			(Desktop::Kde, Action::Suspend, &[ "systemctl", "suspend" ]),
+
+			(Desktop::Logind, Action::Lock, &[ "loginctl", "lock-session" ]),
+			(Desktop::Logind, Action::Logout, &[ "loginctl", "terminate-session", "c2" ]),
+			(Desktop::Logind, Action::Suspend, &[ "systemctl", "suspend" ]),
+			(Desktop::Logind, Action::Hibernate, &[ "systemctl", "hibernate" ]),
+			(Desktop::Logind, Action::Shutdown, &[ "systemctl", "poweroff" ]),
+			(Desktop::Logind, Action::Restart, &[ "systemctl", "reboot" ])
+		];
+
+		for (desktop, action, command) in cases {
+			assert_eq!(run(*desktop, &[], *action), *command, "{:?} {:?}", desktop, action);
+		}
+	}
+
+	#[test]
+	fn custom_commands_override_defaults() {
+		assert_eq!(run(Desktop::Gnome, &[ (Action::Lock, "i3lock -c 000000") ], Action::Lock),
+			vec![ "sh", "-c", "i3lock -c 000000" ]);
+		assert_eq!(run(Desktop::Gnome, &[ (Action::Lock, "i3lock") ], Action::Logout),
+			vec![ "gnome-session-quit", "--logout", "--no-prompt" ]);
+	}
+
+	#[test]
+	fn blank_custom_commands_use_defaults() {
+		assert_eq!(run(Desktop::Logind, &[ (Action::Shutdown, "  ") ], Action::Shutdown), vec![ "systemctl", "poweroff" ]);
+		assert_eq!(run(Desktop::Logind, &[ (Action::Shutdown, "") ], Action::Shutdown), vec![ "systemctl", "poweroff" ]);
+	}
+}
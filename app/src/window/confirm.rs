@@ -0,0 +1,60 @@
+use gtk::prelude::*;
+
+use scout_core::Shared;
+use crate::session::Action;
+
+/** How many seconds to wait before a confirmed action runs on its own. */
+static COUNTDOWN: u32 = 30;
+
+/**
+ * Asks the user to confirm a session action, counting down to running it automatically.
+ * `on_confirm` is called if the action is confirmed, or the countdown runs out.
+ */
+
+pub fn confirm_action<F: FnOnce() + 'static>(action: Action, on_confirm: F) {
+	let dialog = gtk::MessageDialog::new::<gtk::Window>(None, gtk::DialogFlags::MODAL,
+		gtk::MessageType::Question, gtk::ButtonsType::None, &format!("{} now?", action.get_label()));
+	dialog.set_icon_name(Some(action.get_icon()));
+	dialog.set_title(action.get_label());
+	dialog.set_keep_above(true);
+
+	dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+	let confirm = dialog.add_button(action.get_label(), gtk::ResponseType::Accept);
+	confirm.get_style_context().add_class("destructive-action");
+	dialog.set_default_response(gtk::ResponseType::Cancel);
+
+	let describe = move |remaining: u32| format!("{} automatically in {} second{}.",
+		action.get_progressive(), remaining, if remaining == 1 { "" } else { "s" });
+	dialog.set_property_secondary_text(Some(&describe(COUNTDOWN)));
+
+	let timer: Shared<Option<glib::SourceId>> = Shared::new(None);
+	let remaining = Shared::new(COUNTDOWN);
+
+	let timer_clone = timer.clone();
+	let dialog_clone = dialog.clone();
+	timer.replace(Some(glib::timeout_add_seconds_local(1, move || {
+		let left = *remaining.borrow() - 1;
+		remaining.replace(left);
+
+		if left == 0 {
+			timer_clone.replace(None);
+			dialog_clone.response(gtk::ResponseType::Accept);
+			return glib::Continue(false);
+		}
+
+		dialog_clone.set_property_secondary_text(Some(&describe(left)));
+		glib::Continue(true)
+	})));
+
+	let on_confirm = Shared::new(Some(on_confirm));
+	dialog.connect_response(move |dialog, response| {
+		if let Some(timer) = timer.replace(None) { glib::source_remove(timer); }
+
+		dialog.close();
+		if response == gtk::ResponseType::Accept {
+			if let Some(on_confirm) = on_confirm.replace(None) { on_confirm(); }
+		}
+	});
+
+	dialog.show_all();
+}
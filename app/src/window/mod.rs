@@ -6,6 +6,12 @@ pub use prefs_page::*;
 mod window;
 pub use window::*;
 
+mod results_list;
+
+mod confirm;
+
+mod toast;
+
 mod about;
 pub use about::*;
 
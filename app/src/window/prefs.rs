@@ -1,10 +1,8 @@
 use gtk::prelude::*;
-use std::os::unix::process::CommandExt;
 
 use scout_core::Shared;
-use crate::preferences::Preferences;
+use crate::preferences::PreferencesStore;
 
-use super::style;
 use super::prefs_page;
 
 static WIDTH: i32 = 700;
@@ -21,8 +19,13 @@ impl PrefsWindow {
 		self.notebook.append_page(page, Some(&label));
 	}
 
-	pub fn new(preferences: &Preferences) -> Shared<Self> {
-		let preferences = Shared::new(preferences.clone());
+	/**
+	 * Opens the preferences window. Changes are applied immediately,
+	 * and reverted when the window is closed unless they were saved.
+	 */
+
+	pub fn new(preferences: &PreferencesStore) -> Shared<Self> {
+		let saved = Shared::new(preferences.get().clone());
 
 		let window = gtk::Window::new(gtk::WindowType::Toplevel);
 		window.set_widget_name("PreferencesDialog");
@@ -30,7 +33,6 @@ impl PrefsWindow {
 		window.set_title("Scout Preferences");
 		window.set_default_size(WIDTH, HEIGHT);
 		window.set_resizable(false);
-		style::style(&window, &preferences.borrow(), &vec![]);
 
 		let header = gtk::HeaderBar::new();
 		header.set_title(Some("Scout Preferences"));
@@ -50,11 +52,33 @@ impl PrefsWindow {
 		notebook.set_show_border(false);
 		overlay.add(&notebook);
 
+		let saved_clone = saved.clone();
+		let window_clone = window.clone();
 		let preferences_clone = preferences.clone();
 		save_button.connect_clicked(move |_| {
-			preferences_clone.borrow().save().unwrap();
-    	std::process::Command::new("/proc/self/exe").exec();
-    	std::process::exit(0);
+			match preferences_clone.save() {
+				Ok(()) => {
+					saved_clone.replace(preferences_clone.get().clone());
+					window_clone.close();
+				},
+				Err(err) => log::error!("Failed to save preferences: {}", err)
+			}
+		});
+
+		// The config file may be changed by another program while the window is open,
+		// in which case the new file is what closing the window reverts to.
+
+		let saved_weak = saved.get_weak();
+		let listener = preferences.connect_reload(move |prefs|
+			if let Ok(saved) = saved_weak.to_shared() { saved.replace(prefs.clone()); });
+
+		let preferences_clone = preferences.clone();
+		window.connect_destroy(move |_| preferences_clone.disconnect_reload(listener));
+
+		let preferences_clone = preferences.clone();
+		window.connect_delete_event(move |_, _| {
+			preferences_clone.replace(saved.borrow().clone());
+			Inhibit(false)
 		});
 
 	  let prefs = Shared::new(PrefsWindow { notebook });
@@ -63,7 +87,7 @@ impl PrefsWindow {
 		prefs.borrow().add_page("Appearance",	&prefs_page::appearance(preferences.clone()));
 		prefs.borrow().add_page("Plugins",		&prefs_page::plugins(preferences.clone()));
 
-		if preferences.borrow().developer {
+		if preferences.get().developer {
 			prefs.borrow().add_page("Developer",	&prefs_page::developer(preferences.clone()));
 		}
 
@@ -1,9 +1,9 @@
 use gtk::prelude::*;
 
-use scout_core::Shared;
-use crate::preferences::Preferences;
+use crate::preferences::{ Preferences, PreferencesStore, Anchor, Monitor, Layout };
+use crate::window::style;
 
-pub fn appearance(prefs: Shared<Preferences>) -> gtk::Box {
+pub fn appearance(prefs: PreferencesStore) -> gtk::Box {
 	let appearance = gtk::Box::new(gtk::Orientation::Vertical, 0);
 	appearance.set_border_width(12);
 
@@ -13,7 +13,7 @@ pub fn appearance(prefs: Shared<Preferences>) -> gtk::Box {
 	category_label.set_halign(gtk::Align::Start);
 	appearance.pack_start(&category_label, false, false, 2);
 
-	let scale_label = gtk::Label::new(Some(&format!(" Background Opacity  -  {}%", prefs.borrow().opacity)));
+	let scale_label = gtk::Label::new(Some(&format!(" Background Opacity  -  {}%", prefs.get().opacity)));
 	scale_label.set_halign(gtk::Align::Start);
 	appearance.pack_start(&scale_label, false, false, 4);
 
@@ -22,17 +22,159 @@ pub fn appearance(prefs: Shared<Preferences>) -> gtk::Box {
 	scale.set_halign(gtk::Align::Start);
 	scale.set_size_request(300, -1);
 	scale.set_draw_value(false);
-	scale.set_value(prefs.borrow().opacity as f64);
+	scale.set_value(prefs.get().opacity as f64);
 
+	let scale_label_clone = scale_label.clone();
 	let preferences_clone = prefs.clone();
 	scale.connect_change_value(move |_, _, mut val| {
 		val = val.min(100.0);
 		scale_label.set_text(&format!(" Background Opacity  -  {}%", val.floor()));
-		preferences_clone.borrow_mut().opacity = val as u32;
+		preferences_clone.set(|prefs| prefs.opacity = val as u32);
 		Inhibit(false)
 	});
 
 	appearance.pack_start(&scale, false, false, 0);
 
+	let preview = gtk::Label::new(Some("Preview"));
+	preview.set_widget_name("OpacityPreview");
+	preview.set_halign(gtk::Align::Start);
+	preview.set_size_request(300, -1);
+	appearance.pack_start(&preview, false, false, 0);
+
+	let theme_label = gtk::Label::new(Some(" Theme"));
+	theme_label.set_halign(gtk::Align::Start);
+	appearance.pack_start(&theme_label, false, false, 4);
+
+	let theme_box = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+	appearance.pack_start(&theme_box, false, false, 0);
+
+	let theme_picker = gtk::ComboBoxText::new();
+	theme_picker.set_size_request(250, -1);
+	theme_box.pack_start(&theme_picker, false, false, 0);
+
+	fn populate_themes(picker: &gtk::ComboBoxText, current: Option<&str>) {
+		picker.remove_all();
+		picker.append(Some(""), "Default");
+		for theme in style::list_themes() { picker.append(Some(&theme), &theme); }
+		if !picker.set_active_id(Some(current.unwrap_or(""))) { picker.set_active_id(Some("")); }
+	}
+
+	populate_themes(&theme_picker, prefs.get().theme.as_deref());
+
+	let preferences_clone = prefs.clone();
+	theme_picker.connect_changed(move |picker| {
+		let theme = match picker.get_active_id() { Some(id) => id.to_string(), None => return };
+		let theme = if theme.is_empty() { None } else { Some(theme) };
+		if preferences_clone.get().theme != theme { preferences_clone.set(|prefs| prefs.theme = theme); }
+	});
+
+	let reload_button = gtk::Button::from_icon_name(Some("view-refresh-symbolic"), gtk::IconSize::Button);
+	reload_button.set_tooltip_text(Some("Reload Theme"));
+	theme_box.pack_start(&reload_button, false, false, 0);
+
+	let theme_picker_clone = theme_picker.clone();
+	let preferences_clone = prefs.clone();
+	reload_button.connect_clicked(move |_| {
+		let theme = preferences_clone.get().theme.clone();
+		populate_themes(&theme_picker_clone, theme.as_deref());
+		preferences_clone.refresh();
+	});
+
+	let themes_label = gtk::Label::new(None);
+	themes_label.set_markup(&format!("<small> Themes are loaded from <tt>{}</tt></small>",
+		glib::markup_escape_text(&style::themes_dir().to_string_lossy())));
+	themes_label.set_halign(gtk::Align::Start);
+	appearance.pack_start(&themes_label, false, false, 4);
+
+	let geometry_grid = gtk::Grid::new();
+	geometry_grid.set_row_spacing(6);
+	geometry_grid.set_column_spacing(12);
+	geometry_grid.set_margin_top(18);
+	appearance.pack_start(&geometry_grid, false, false, 0);
+
+	fn add_row<T: IsA<gtk::Widget>>(grid: &gtk::Grid, row: i32, label: &str, widget: &T) {
+		let label = gtk::Label::new(Some(label));
+		label.set_halign(gtk::Align::Start);
+		grid.attach(&label, 0, row, 1, 1);
+		grid.attach(widget, 1, row, 1, 1);
+	}
+
+	fn add_spin(grid: &gtk::Grid, row: i32, label: &str, min: f64, max: f64, prefs: &PreferencesStore,
+		get: fn(&Preferences) -> i32, set: fn(&mut Preferences, i32)) -> gtk::SpinButton {
+		let spin = gtk::SpinButton::with_range(min, max, 10.0);
+		spin.set_value(get(&prefs.get()) as f64);
+		add_row(grid, row, label, &spin);
+
+		let preferences_clone = prefs.clone();
+		spin.connect_value_changed(move |spin| preferences_clone.set(|prefs| set(prefs, spin.get_value_as_int())));
+		spin
+	}
+
+	fn layout_id(layout: Layout) -> &'static str { match layout { Layout::Full => "full", Layout::Compact => "compact" } }
+
+	fn anchor_id(anchor: Anchor) -> &'static str { match anchor { Anchor::Center => "center", Anchor::TopThird => "top_third" } }
+
+	fn monitor_id(monitor: Monitor) -> &'static str { match monitor { Monitor::Primary => "primary", Monitor::Pointer => "pointer" } }
+
+	let layout_picker = gtk::ComboBoxText::new();
+	layout_picker.append(Some("full"), "Results and Preview");
+	layout_picker.append(Some("compact"), "Compact List");
+	layout_picker.set_active_id(Some(layout_id(prefs.get().layout)));
+	add_row(&geometry_grid, 0, " Layout", &layout_picker);
+
+	let layout_picker_clone = layout_picker.clone();
+	let preferences_clone = prefs.clone();
+	layout_picker.connect_changed(move |picker| {
+		let layout = match picker.get_active_id().as_deref() { Some("compact") => Layout::Compact, _ => Layout::Full };
+		preferences_clone.set(|prefs| prefs.layout = layout);
+	});
+
+	let width_spin = add_spin(&geometry_grid, 1, " Window Width", 400.0, 2000.0, &prefs, |p| p.width, |p, v| p.width = v);
+	let height_spin = add_spin(&geometry_grid, 2, " Window Height", 200.0, 1500.0, &prefs, |p| p.height, |p, v| p.height = v);
+	let results_width_spin = add_spin(&geometry_grid, 3, " Results Width", 120.0, 800.0, &prefs,
+		|p| p.results_width, |p, v| p.results_width = v);
+
+	let anchor_picker = gtk::ComboBoxText::new();
+	anchor_picker.append(Some("center"), "Centered");
+	anchor_picker.append(Some("top_third"), "Top Third");
+	anchor_picker.set_active_id(Some(anchor_id(prefs.get().anchor)));
+	add_row(&geometry_grid, 4, " Position", &anchor_picker);
+
+	let anchor_picker_clone = anchor_picker.clone();
+	let preferences_clone = prefs.clone();
+	anchor_picker.connect_changed(move |picker| {
+		let anchor = match picker.get_active_id().as_deref() { Some("top_third") => Anchor::TopThird, _ => Anchor::Center };
+		preferences_clone.set(|prefs| prefs.anchor = anchor);
+	});
+
+	let monitor_picker = gtk::ComboBoxText::new();
+	monitor_picker.append(Some("primary"), "Primary Monitor");
+	monitor_picker.append(Some("pointer"), "Monitor Under Pointer");
+	monitor_picker.set_active_id(Some(monitor_id(prefs.get().monitor)));
+	add_row(&geometry_grid, 5, " Monitor", &monitor_picker);
+
+	let monitor_picker_clone = monitor_picker.clone();
+	let preferences_clone = prefs.clone();
+	monitor_picker.connect_changed(move |picker| {
+		let monitor = match picker.get_active_id().as_deref() { Some("pointer") => Monitor::Pointer, _ => Monitor::Primary };
+		preferences_clone.set(|prefs| prefs.monitor = monitor);
+	});
+
+	// Show the new preferences if the config file is changed by another program while the page is open.
+	let listener = prefs.connect_reload(move |reloaded| {
+		scale.set_value(reloaded.opacity as f64);
+		scale_label_clone.set_text(&format!(" Background Opacity  -  {}%", reloaded.opacity));
+		populate_themes(&theme_picker, reloaded.theme.as_deref());
+		layout_picker_clone.set_active_id(Some(layout_id(reloaded.layout)));
+		width_spin.set_value(reloaded.width as f64);
+		height_spin.set_value(reloaded.height as f64);
+		results_width_spin.set_value(reloaded.results_width as f64);
+		anchor_picker_clone.set_active_id(Some(anchor_id(reloaded.anchor)));
+		monitor_picker_clone.set_active_id(Some(monitor_id(reloaded.monitor)));
+	});
+
+	appearance.connect_destroy(move |_| prefs.disconnect_reload(listener));
+
+
 	appearance
 }
@@ -1,9 +1,8 @@
 use gtk::prelude::*;
 
-use scout_core::Shared;
-use crate::preferences::Preferences;
+use crate::preferences::PreferencesStore;
 
-pub fn behavior(prefs: Shared<Preferences>) -> gtk::Box {
+pub fn behavior(prefs: PreferencesStore) -> gtk::Box {
 	let behavior = gtk::Box::new(gtk::Orientation::Vertical, 0);
 	behavior.set_border_width(14);
 
@@ -14,18 +13,35 @@ pub fn behavior(prefs: Shared<Preferences>) -> gtk::Box {
 	behavior.pack_start(&category_label, false, false, 0);
 
 	let unfocused_button = gtk::CheckButton::with_label("   Hide when the window is unfocused");
-	unfocused_button.set_active(prefs.borrow().hide_on_unfocus);
+	unfocused_button.set_active(prefs.get().hide_on_unfocus);
 	behavior.pack_start(&unfocused_button, false, false, 4);
 
 	let preferences_clone = prefs.clone();
-	unfocused_button.connect_toggled(move |s| preferences_clone.borrow_mut().hide_on_unfocus = s.get_active());
+	unfocused_button.connect_toggled(move |s| preferences_clone.set(|prefs| prefs.hide_on_unfocus = s.get_active()));
 
 	let aot_button = gtk::CheckButton::with_label("   Keep window above other windows");
-	aot_button.set_active(prefs.borrow().always_on_top);
+	aot_button.set_active(prefs.get().always_on_top);
 	behavior.pack_start(&aot_button, false, false, 4);
 
 	let preferences_clone = prefs.clone();
-	aot_button.connect_toggled(move |s| preferences_clone.borrow_mut().always_on_top = s.get_active());
+	aot_button.connect_toggled(move |s| preferences_clone.set(|prefs| prefs.always_on_top = s.get_active()));
+
+	let log_button = gtk::CheckButton::with_label("   Record searches locally to evaluate ranking");
+	log_button.set_active(prefs.get().log_queries);
+	log_button.set_tooltip_text(Some("Searches and the results chosen are saved to queries.jsonl in Scout's data directory."));
+	behavior.pack_start(&log_button, false, false, 4);
+
+	let preferences_clone = prefs.clone();
+	log_button.connect_toggled(move |s| preferences_clone.set(|prefs| prefs.log_queries = s.get_active()));
+
+	// Show the new preferences if the config file is changed by another program while the page is open.
+	let listener = prefs.connect_reload(move |reloaded| {
+		unfocused_button.set_active(reloaded.hide_on_unfocus);
+		aot_button.set_active(reloaded.always_on_top);
+		log_button.set_active(reloaded.log_queries);
+	});
+
+	behavior.connect_destroy(move |_| prefs.disconnect_reload(listener));
 
 	behavior
 }
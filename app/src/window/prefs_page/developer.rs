@@ -1,9 +1,15 @@
 use gtk::prelude::*;
+use std::collections::VecDeque;
 
 use scout_core::Shared;
-use crate::preferences::Preferences;
+use crate::logging::{ self, LogLine };
+use crate::plugin::profiler;
+use crate::preferences::PreferencesStore;
 
-pub fn developer(_prefs: Shared<Preferences>) -> gtk::Box {
+/** The levels that can be selected in the log viewer, from least to most verbose. */
+static LEVELS: [log::Level; 5] = [ log::Level::Error, log::Level::Warn, log::Level::Info, log::Level::Debug, log::Level::Trace ];
+
+pub fn developer(_prefs: PreferencesStore) -> gtk::Box {
 	let developer = gtk::Box::new(gtk::Orientation::Vertical, 0);
 	developer.set_border_width(12);
 
@@ -16,7 +22,226 @@ pub fn developer(_prefs: Shared<Preferences>) -> gtk::Box {
 	let swatches = gtk::Box::new(gtk::Orientation::Vertical, 0);
 	swatches.set_widget_name("SwatchTest");
 	for i in 0..10 { swatches.pack_start(&gtk::Label::new(Some(&format!("@c-neutral-{}00", i))), false, false, 0); }
-	developer.pack_start(&swatches, true, true, 0);
+	for name in &[ "@c-accent", "@c-accent-hover" ] { swatches.pack_start(&gtk::Label::new(Some(name)), false, false, 0); }
+	developer.pack_start(&swatches, false, false, 0);
+
+	developer.pack_start(&plugin_timings(), false, false, 12);
+	developer.pack_start(&ranking_debugger(), false, false, 0);
+	developer.pack_start(&log_viewer(), true, true, 12);
 
 	developer
 }
+
+/**
+ * Formats a duration in milliseconds, to a tenth of a millisecond.
+ */
+
+fn format_ms(duration: std::time::Duration) -> String {
+	format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+/**
+ * Creates a table of each plugin's recent query and widget timings, which is refreshed every second.
+ */
+
+fn plugin_timings() -> gtk::Grid {
+	let grid = gtk::Grid::new();
+	grid.set_widget_name("PluginTimings");
+	grid.set_column_spacing(18);
+	grid.set_row_spacing(4);
+
+	let grid_clone = grid.clone();
+	let refresh = move || {
+		grid_clone.get_children().iter().for_each(|child| grid_clone.remove(child));
+
+		let headers = [ "Plugin", "Queries", "Last", "P95", "Results", "Widget P95" ];
+		for (i, header) in headers.iter().enumerate() {
+			let label = gtk::Label::new(None);
+			label.set_markup(&[ "<b>", header, "</b>" ].join(""));
+			label.set_xalign(if i == 0 { 0.0 } else { 1.0 });
+			grid_clone.attach(&label, i as i32, 0, 1, 1);
+		}
+
+		for (row, stats) in profiler::get_stats().into_iter().enumerate() {
+			let cells = [
+				stats.identifier,
+				stats.queries.to_string(),
+				format_ms(stats.last_query),
+				format_ms(stats.p95_query),
+				stats.last_count.to_string(),
+				stats.p95_widget.map(format_ms).unwrap_or_else(|| "-".to_owned())
+			];
+
+			for (i, cell) in cells.iter().enumerate() {
+				let label = gtk::Label::new(Some(cell));
+				label.set_xalign(if i == 0 { 0.0 } else { 1.0 });
+				grid_clone.attach(&label, i as i32, row as i32 + 1, 1, 1);
+			}
+		}
+
+		grid_clone.show_all();
+	};
+	refresh();
+
+	let source = Shared::new(Some(glib::timeout_add_seconds_local(1, move || { refresh(); glib::Continue(true) })));
+	grid.connect_destroy(move |_| { if let Some(source) = source.replace(None) { glib::source_remove(source); } });
+
+	grid
+}
+
+/**
+ * Creates a table of every candidate result for the latest query, showing how each was scored and ranked.
+ * Results that were dropped for scoring under 75% of the top result are marked.
+ */
+
+fn ranking_debugger() -> gtk::Box {
+	let debugger = gtk::Box::new(gtk::Orientation::Vertical, 6);
+	debugger.set_widget_name("RankingDebugger");
+
+	let query_label = gtk::Label::new(None);
+	query_label.set_xalign(0.0);
+	debugger.pack_start(&query_label, false, false, 0);
+
+	let scroller = gtk::ScrolledWindow::new::<gtk::Adjustment, gtk::Adjustment>(None, None);
+	scroller.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+	scroller.set_size_request(-1, 200);
+	debugger.pack_start(&scroller, false, false, 0);
+
+	let grid = gtk::Grid::new();
+	grid.set_column_spacing(18);
+	grid.set_row_spacing(4);
+	scroller.add(&grid);
+
+	let generation = Shared::new(None);
+	let refresh = move || {
+		let ranking = profiler::get_ranking();
+		if *generation.borrow() == Some(ranking.generation) { return; }
+		generation.replace(Some(ranking.generation));
+
+		query_label.set_markup(&format!("<b>Ranking for</b> '{}'", glib::markup_escape_text(&ranking.query)));
+		grid.get_children().iter().for_each(|child| grid.remove(child));
+
+		let headers = [ "Position", "Plugin", "Title", "Score", "Boost", "Normalized" ];
+		for (i, header) in headers.iter().enumerate() {
+			let label = gtk::Label::new(None);
+			label.set_markup(&[ "<b>", header, "</b>" ].join(""));
+			label.set_xalign(if i == 1 || i == 2 { 0.0 } else { 1.0 });
+			grid.attach(&label, i as i32, 0, 1, 1);
+		}
+
+		for (row, candidate) in ranking.candidates.iter().enumerate() {
+			let cells = [
+				candidate.position.map(|position| (position + 1).to_string()).unwrap_or_else(|| "Dropped".to_owned()),
+				candidate.plugin.clone(),
+				candidate.title.clone(),
+				candidate.score.to_string(),
+				candidate.boost.to_string(),
+				format!("{:.2}", candidate.normalized)
+			];
+
+			for (i, cell) in cells.iter().enumerate() {
+				let label = gtk::Label::new(Some(cell));
+				label.set_xalign(if i == 1 || i == 2 { 0.0 } else { 1.0 });
+				label.set_ellipsize(if i == 2 { pango::EllipsizeMode::End } else { pango::EllipsizeMode::None });
+				label.set_hexpand(i == 2);
+				label.set_sensitive(candidate.position.is_some());
+				grid.attach(&label, i as i32, row as i32 + 1, 1, 1);
+			}
+		}
+
+		grid.show_all();
+	};
+	refresh();
+
+	let source = Shared::new(Some(glib::timeout_add_local(250, move || { refresh(); glib::Continue(true) })));
+	debugger.connect_destroy(move |_| { if let Some(source) = source.replace(None) { glib::source_remove(source); } });
+
+	debugger
+}
+
+/**
+ * Creates a view of Scout's log, which updates as lines are logged.
+ * Lines can be filtered by their minimum level, and by text.
+ */
+
+fn log_viewer() -> gtk::Box {
+	let viewer = gtk::Box::new(gtk::Orientation::Vertical, 6);
+	viewer.set_widget_name("LogViewer");
+
+	let controls = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+	viewer.pack_start(&controls, false, false, 0);
+
+	let search = gtk::SearchEntry::new();
+	search.set_placeholder_text(Some("Filter logs"));
+	controls.pack_start(&search, true, true, 0);
+
+	let level = gtk::ComboBoxText::new();
+	for l in LEVELS.iter() { level.append(Some(&l.to_string()), &l.to_string()); }
+	level.set_active_id(Some(&log::Level::Info.to_string()));
+	controls.pack_start(&level, false, false, 0);
+
+	let scroller = gtk::ScrolledWindow::new::<gtk::Adjustment, gtk::Adjustment>(None, None);
+	scroller.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+	scroller.set_size_request(-1, 240);
+	viewer.pack_start(&scroller, true, true, 0);
+
+	let text = gtk::TextView::new();
+	text.set_editable(false);
+	text.set_cursor_visible(false);
+	text.set_monospace(true);
+	scroller.add(&text);
+
+	let (recent, receiver) = logging::subscribe();
+	let lines = Shared::new(recent.into_iter().collect::<VecDeque<_>>());
+
+	let matches = {
+		let search = search.clone();
+		let level = level.clone();
+		move |line: &LogLine| {
+			let max_level = level.get_active_id().and_then(|id| id.parse::<log::Level>().ok()).unwrap_or(log::Level::Info);
+			let filter = search.get_text().to_lowercase();
+			line.level <= max_level && (filter.is_empty() || line.to_string().to_lowercase().contains(&filter))
+		}
+	};
+
+	let render = {
+		let text = text.clone();
+		let lines = lines.clone();
+		let matches = matches.clone();
+		move || {
+			let contents = lines.borrow().iter().filter(|line| matches(line))
+				.map(|line| line.to_string()).collect::<Vec<_>>().join("\n");
+			if let Some(buffer) = text.get_buffer() { buffer.set_text(&contents); }
+		}
+	};
+	render();
+
+	let render_clone = render.clone();
+	search.connect_search_changed(move |_| render_clone());
+	level.connect_changed(move |_| render());
+
+	let text_clone = text.clone();
+	let source = receiver.attach(None, move |line| {
+		if matches(&line) {
+			if let Some(buffer) = text_clone.get_buffer() {
+				let mut end = buffer.get_end_iter();
+				let prefix = if buffer.get_char_count() > 0 { "\n" } else { "" };
+				buffer.insert(&mut end, &[ prefix, &line.to_string() ].join(""));
+
+				while buffer.get_line_count() > logging::MAX_LINES as i32 {
+					buffer.delete(&mut buffer.get_start_iter(), &mut buffer.get_iter_at_line(1));
+				}
+			}
+		}
+
+		let mut lines = lines.borrow_mut();
+		if lines.len() >= logging::MAX_LINES { lines.pop_front(); }
+		lines.push_back(line);
+		glib::Continue(true)
+	});
+
+	let source = Shared::new(Some(source));
+	viewer.connect_destroy(move |_| { if let Some(source) = source.replace(None) { glib::source_remove(source); } });
+
+	viewer
+}
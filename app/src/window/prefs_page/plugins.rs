@@ -1,9 +1,8 @@
 use gtk::prelude::*;
 
-use scout_core::Shared;
-use crate::preferences::Preferences;
+use crate::preferences::PreferencesStore;
 
-pub fn plugins(_prefs: Shared<Preferences>) -> gtk::Box {
+pub fn plugins(_prefs: PreferencesStore) -> gtk::Box {
 	let plugins = gtk::Box::new(gtk::Orientation::Vertical, 0);
 	plugins.set_border_width(12);
 
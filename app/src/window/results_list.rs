@@ -0,0 +1,135 @@
+use gtk::prelude::*;
+use std::collections::{ HashMap, HashSet };
+
+use crate::plugin::{ profiler, PluginResult };
+
+/** How many rows are created at once. More are added as the list is scrolled. */
+static PAGE_SIZE: usize = 16;
+
+/** How many rows may be kept for reuse before rows for results that are no longer shown are dropped. */
+static CACHE_SIZE: usize = 64;
+
+/**
+ * A list of search results, which only creates rows for results that are scrolled into view,
+ * and reuses rows for results that appear in consecutive queries.
+ */
+
+pub struct ResultsList {
+	list: gtk::ListBox,
+	results: Vec<PluginResult>,
+	rows: HashMap<String, gtk::ListBoxRow>,
+	shown: usize
+}
+
+impl ResultsList {
+	pub fn new() -> Self {
+		let list = gtk::ListBox::new();
+		list.set_widget_name("ResultsList");
+		list.set_selection_mode(gtk::SelectionMode::None);
+
+		ResultsList { list, results: vec![], rows: HashMap::new(), shown: 0 }
+	}
+
+	pub fn get_widget(&self) -> &gtk::ListBox {
+		&self.list
+	}
+
+	/**
+	 * Finds a displayed result by its identifier, or returns the first result if the identifier is empty.
+	 */
+
+	pub fn find(&self, id: &str) -> Option<&PluginResult> {
+		if id.is_empty() { self.results.first() }
+		else { self.results.iter().find(|res| res.result.get_id() == id) }
+	}
+
+	pub fn get_results(&self) -> &[PluginResult] {
+		&self.results
+	}
+
+	/**
+	 * Returns the result displayed in a row of the list.
+	 */
+
+	pub fn get_row_result(&self, row: &gtk::ListBoxRow) -> Option<&PluginResult> {
+		let index = row.get_index();
+		if index < 0 { None } else { self.results.get(index as usize) }
+	}
+
+	/**
+	 * Removes all results, and drops every cached row.
+	 * This must be called before the plugins that created the rows are unloaded.
+	 */
+
+	pub fn clear(&mut self) {
+		self.set_results(vec![]);
+		self.rows.clear();
+	}
+
+	/**
+	 * Takes the displayed results, except for those from the plugin specified, leaving the list empty.
+	 * Cached rows for the plugin's results are dropped, so they are recreated when its new results are shown.
+	 */
+
+	pub fn take_results_except(&mut self, plugin: &str) -> Vec<PluginResult> {
+		let prefix = [ plugin, "\0" ].join("");
+		self.rows.retain(|key, _| !key.starts_with(&prefix));
+
+		let mut results = std::mem::replace(&mut self.results, vec![]);
+		results.retain(|res| res.plugin != plugin);
+		results
+	}
+
+	/**
+	 * Returns the key a result's row is cached under.
+	 */
+
+	fn get_key(res: &PluginResult) -> String {
+		[ &res.plugin, "\0", &res.result.get_id() ].join("")
+	}
+
+	/**
+	 * Replaces the displayed results, creating rows for the first page.
+	 */
+
+	pub fn set_results(&mut self, results: Vec<PluginResult>) {
+		self.list.get_children().iter().for_each(|c| self.list.remove(c));
+
+		if self.rows.len() > CACHE_SIZE {
+			let ids = results.iter().map(ResultsList::get_key).collect::<HashSet<_>>();
+			self.rows.retain(|id, _| ids.contains(id));
+		}
+
+		self.results = results;
+		self.shown = 0;
+		self.show_more();
+	}
+
+	/**
+	 * Creates rows for the next page of results, if there are any left.
+	 */
+
+	pub fn show_more(&mut self) {
+		let end = (self.shown + PAGE_SIZE).min(self.results.len());
+
+		for (i, res) in self.results[self.shown..end].iter().enumerate() {
+			let rows = &mut self.rows;
+			let cached = rows.get(&ResultsList::get_key(res)).filter(|row| row.get_parent().is_none()).cloned();
+			let row = cached.unwrap_or_else(|| {
+				let row = gtk::ListBoxRow::new();
+				row.set_activatable(false);
+				row.set_selectable(false);
+				row.set_can_focus(false);
+				row.add(&profiler::time_widget(&res.plugin, || res.result.get_result_widget()));
+				rows.insert(ResultsList::get_key(res), row.clone());
+				row
+			});
+
+			res.result.set_first(&row.get_child().unwrap(), self.shown + i == 0);
+			self.list.add(&row);
+		}
+
+		self.shown = end;
+		self.list.show_all();
+	}
+}
@@ -2,11 +2,125 @@
 
 use gtk::prelude::*;
 
+use std::path::PathBuf;
+
+use scout_core::Shared;
+
+use crate::paths;
 use crate::preferences::Preferences;
 
-pub fn style<T: IsA<gtk::Widget>>(window: &T, prefs: &Preferences, styles: &Vec<&'static str>) {
-	let provider = gtk::CssProvider::new();
+/**
+ * Returns the directory user themes are loaded from, `$XDG_CONFIG_HOME/scout/themes`.
+ */
+
+pub fn themes_dir() -> PathBuf {
+	paths::config_dir().join("themes")
+}
+
+/**
+ * Lists the file names of the themes in the themes directory.
+ * Themes are CSS files, or SCSS / Sass files if the `sass` compiler is installed.
+ */
+
+pub fn list_themes() -> Vec<String> {
+	let mut themes = std::fs::read_dir(themes_dir()).map(|dir| dir
+		.filter_map(|entry| entry.ok())
+		.map(|entry| entry.path())
+		.filter(|path| path.is_file())
+		.filter(|path| matches!(path.extension().and_then(|ext| ext.to_str()), Some("css") | Some("scss") | Some("sass")))
+		.filter_map(|path| path.file_name().and_then(|name| name.to_str()).map(|name| name.to_owned()))
+		.collect::<Vec<_>>()).unwrap_or_default();
+
+	themes.sort();
+	themes
+}
+
+/**
+ * Reads a theme from the themes directory, compiling it with `sass` if it isn't plain CSS.
+ */
+
+fn load_theme(name: &str) -> std::io::Result<String> {
+	let path = themes_dir().join(name);
+
+	match path.extension().and_then(|ext| ext.to_str()) {
+		Some("scss") | Some("sass") => {
+			let output = std::process::Command::new("sass").arg("--no-source-map").arg(&path).output()?;
+			if !output.status.success() {
+				return Err(std::io::Error::new(std::io::ErrorKind::InvalidData,
+					String::from_utf8_lossy(&output.stderr).trim().to_owned()));
+			}
+			Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+		},
+		_ => std::fs::read_to_string(&path)
+	}
+}
+
+/**
+ * The app's stylesheet, which is regenerated whenever the preferences change.
+ * One provider is shared by every window on the screen, so restyling doesn't stack providers.
+ * The palette read from the GTK theme is kept until the theme changes, as reading it creates widgets.
+ */
+
+pub struct Style {
+	provider: gtk::CssProvider,
+	styles: Vec<String>,
+	palette: Shared<Option<String>>
+}
+
+impl Style {
+	pub fn new(styles: Vec<String>) -> Self {
+		let provider = gtk::CssProvider::new();
+		gtk::StyleContext::add_provider_for_screen(&gdk::Screen::get_default().expect("Error initializing GTK CSS provider."),
+			&provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+
+		Style { provider, styles, palette: Shared::new(None) }
+	}
+
+	/**
+	 * Regenerates the stylesheet, using the colors of the window provided.
+	 * The user's theme is loaded last, so it can override any rule or `@c-*` color.
+	 * If the theme can't be loaded, the default style is used instead.
+	 */
+
+	pub fn update<T: IsA<gtk::Widget>>(&self, window: &T, prefs: &Preferences) {
+		if self.palette.borrow().is_none() { self.palette.replace(Some(palette(window))); }
+		let s = style(self.palette.borrow().as_deref().unwrap_or(""), prefs, &self.styles);
 
+		if let Some(name) = &prefs.theme {
+			let loaded = load_theme(name).and_then(|theme| self.provider.load_from_data([ &s, "\n", &theme ].concat().as_bytes())
+				.map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())));
+
+			match loaded {
+				Ok(()) => return,
+				Err(err) => log::warn!("Failed to load theme '{}': {}", name, err)
+			}
+		}
+
+		self.provider.load_from_data(s.as_bytes()).expect("Failed to load CSS.");
+	}
+
+	/**
+	 * Replaces the plugins' stylesheets, which are used from the next update.
+	 */
+
+	pub fn set_styles(&mut self, styles: Vec<String>) {
+		self.styles = styles;
+	}
+
+	/**
+	 * Reads the palette from the GTK theme again on the next update, after the theme has changed.
+	 */
+
+	pub fn invalidate_palette(&self) {
+		self.palette.replace(None);
+	}
+}
+
+/**
+ * Reads the base colors of the GTK theme, from the window and from widgets the theme styles.
+ */
+
+fn palette<T: IsA<gtk::Widget>>(window: &T) -> String {
 	let mut s = String::new();
 
 	let mut add_color = |identifier: &str, color: &gdk::RGBA| {
@@ -20,10 +134,29 @@ pub fn style<T: IsA<gtk::Widget>>(window: &T, prefs: &Preferences, styles: &Vec<
 	let entry = gtk::Entry::new();
 	let button = gtk::Button::new();
 
+	let context = window.get_style_context();
+
 	add_color("c-neutral-000", &button.get_style_context().get_border_color(gtk::StateFlags::NORMAL));
-	add_color("c-neutral-100", &window.get_style_context().get_background_color(gtk::StateFlags::NORMAL));
+	add_color("c-neutral-100", &context.get_background_color(gtk::StateFlags::NORMAL));
 	add_color("c-neutral-900", &entry.get_style_context().get_color(gtk::StateFlags::NORMAL));
 
+	// Accent colors are taken from the theme's selection colors, which every GTK theme defines.
+
+	add_color("c-accent", &context.lookup_color("theme_selected_bg_color")
+		.unwrap_or(gdk::RGBA { red: 0.21, green: 0.52, blue: 0.89, alpha: 1.0 }));
+	add_color("c-accent-text", &context.lookup_color("theme_selected_fg_color")
+		.unwrap_or(gdk::RGBA { red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0 }));
+
+	s
+}
+
+fn style(palette: &str, prefs: &Preferences, styles: &[String]) -> String {
+	let mut s = palette.to_owned();
+
+	// The rest of the neutral ramp is mixed from the background and text colors in the stylesheet.
+
+	s.push_str("@define-color c-accent-hover mix(@c-accent, @c-neutral-900, 0.15);\n");
+
 	s.push_str("@define-color c-background-primary alpha(@c-neutral-100, ");
 	s.push_str(&(prefs.opacity as f64 / 100.0).to_string());
 	s.push_str(");");
@@ -39,7 +172,5 @@ pub fn style<T: IsA<gtk::Widget>>(window: &T, prefs: &Preferences, styles: &Vec<
 		s.push_str(style);
 	}
 
-	provider.load_from_data(s.as_bytes()).expect("Failed to load CSS.");
-	gtk::StyleContext::add_provider_for_screen(&gdk::Screen::get_default().expect("Error initializing GTK CSS provider."),
-		&provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+	s
 }
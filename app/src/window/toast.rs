@@ -0,0 +1,113 @@
+use gtk::prelude::*;
+
+use scout_core::{ Shared, Notification, NotifyLevel };
+
+/** How long a toast is shown for, in seconds. Errors are shown for twice as long. */
+static TIMEOUT: u32 = 4;
+
+/**
+ * A toast shown at the bottom of the window, which displays one notification at a time.
+ * Newer notifications replace the one being shown.
+ */
+
+pub struct Toast {
+	revealer: gtk::Revealer,
+	bar: gtk::InfoBar,
+	label: gtk::Label,
+	action: gtk::Button,
+	callback: Shared<Option<std::sync::Arc<dyn Fn() + Send + Sync>>>,
+	timer: Shared<Option<glib::SourceId>>
+}
+
+impl Toast {
+	pub fn new() -> Self {
+		let revealer = gtk::Revealer::new();
+		revealer.set_widget_name("Toast");
+		revealer.set_transition_type(gtk::RevealerTransitionType::SlideUp);
+		revealer.set_halign(gtk::Align::Center);
+		revealer.set_valign(gtk::Align::End);
+
+		let bar = gtk::InfoBar::new();
+		bar.set_show_close_button(true);
+		revealer.add(&bar);
+
+		let label = gtk::Label::new(None);
+		label.set_line_wrap(true);
+		label.set_xalign(0.0);
+		bar.get_content_area().add(&label);
+
+		let action = gtk::Button::new();
+		action.set_no_show_all(true);
+		bar.add_action_widget(&action, gtk::ResponseType::Accept);
+
+		let callback: Shared<Option<std::sync::Arc<dyn Fn() + Send + Sync>>> = Shared::new(None);
+		let timer: Shared<Option<glib::SourceId>> = Shared::new(None);
+
+		let revealer_clone = revealer.clone();
+		let callback_clone = callback.clone();
+		let timer_clone = timer.clone();
+		bar.connect_response(move |_, response| {
+			if response == gtk::ResponseType::Accept {
+				let callback = callback_clone.borrow().clone();
+				if let Some(callback) = callback { callback(); }
+			}
+
+			if let Some(timer) = timer_clone.replace(None) { glib::source_remove(timer); }
+			revealer_clone.set_reveal_child(false);
+		});
+
+		revealer.show_all();
+		Toast { revealer, bar, label, action, callback, timer }
+	}
+
+	pub fn get_widget(&self) -> &gtk::Revealer {
+		&self.revealer
+	}
+
+	/**
+	 * Shows a notification, hiding it after a few seconds.
+	 */
+
+	pub fn show(&self, notification: &Notification) {
+		self.bar.set_message_type(match notification.level {
+			NotifyLevel::Info => gtk::MessageType::Info,
+			NotifyLevel::Warning => gtk::MessageType::Warning,
+			NotifyLevel::Error => gtk::MessageType::Error
+		});
+
+		self.label.set_text(&notification.message);
+
+		match &notification.action {
+			Some(action) => {
+				self.action.set_label(&action.label);
+				self.action.show();
+			},
+			None => self.action.hide()
+		}
+		self.callback.replace(notification.action.as_ref().map(|action| action.callback.clone()));
+
+		if let Some(timer) = self.timer.replace(None) { glib::source_remove(timer); }
+		let timeout = if notification.level == NotifyLevel::Error { TIMEOUT * 2 } else { TIMEOUT };
+
+		let revealer = self.revealer.clone();
+		let timer = self.timer.clone();
+		self.timer.replace(Some(glib::timeout_add_seconds_local(timeout, move || {
+			timer.replace(None);
+			revealer.set_reveal_child(false);
+			glib::Continue(false)
+		})));
+
+		self.revealer.set_reveal_child(true);
+	}
+
+	/**
+	 * Hides the notification being shown, if there is one.
+	 * Its action is dropped, as it may belong to a plugin that is about to be unloaded.
+	 */
+
+	pub fn hide(&self) {
+		if let Some(timer) = self.timer.replace(None) { glib::source_remove(timer); }
+		self.callback.replace(None);
+		self.revealer.set_reveal_child(false);
+	}
+}
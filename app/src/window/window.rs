@@ -1,24 +1,28 @@
 use gio::prelude::*;
 use gtk::prelude::*;
-use scout_core::{ SearchResult };
 // use glib::translate::{ ToGlib, FromGlib };
 
 use super::about;
 use super::style;
+use super::toast;
+use super::confirm;
 use super::prefs::PrefsWindow;
+use super::results_list::ResultsList;
 
-use scout_core::Shared;
+use scout_core::{ Shared, Notification };
 // use crate::plugins::Plugins;
-use crate::preferences::Preferences;
-
-static DIMENSIONS: (i32, i32) = ( 700, 500 );
+use crate::history::Boosts;
+use crate::plugin::{ profiler, PluginParser, PluginResult, RankingConfig };
+use crate::session::{ Action, Session };
+use crate::preferences::{ Preferences, PreferencesStore, Anchor, Monitor, Layout };
 
 pub struct WindowCallbacks {
 	pub on_input: Box<dyn FnMut(&str)>,
 	pub on_submit: Box<dyn FnMut()>,
 	// on_clear: Box<dyn FnMut()>,
-	// on_show: Box<dyn FnMut()>,
-	// on_hide: Box<dyn FnMut()>
+	pub on_activate: Box<dyn FnMut(&str, &str)>,
+	pub on_show: Box<dyn FnMut()>,
+	pub on_hide: Box<dyn FnMut()>
 }
 
 impl Default for WindowCallbacks {
@@ -26,44 +30,48 @@ impl Default for WindowCallbacks {
 		WindowCallbacks {
 			on_input: Box::new(|_| ()),
 			on_submit: Box::new(|| ()),
+			on_activate: Box::new(|_, _| ()),
+			on_show: Box::new(|| ()),
+			on_hide: Box::new(|| ())
 		}
 	}
 }
 
 pub struct Window {
 	window: gtk::ApplicationWindow,
+	header: gtk::Fixed,
+	profile: gtk::Button,
 	search_entry: gtk::Entry,
-	results_box: gtk::Box,
+	results: Shared<ResultsList>,
 	results_scroller: gtk::ScrolledWindow,
+	details: gtk::Label,
+	preview: gtk::Box,
 	preview_scroller: gtk::ScrolledWindow,
+	toast: toast::Toast,
 
 	callbacks: Shared<WindowCallbacks>,
-	preferences: Shared<Preferences>,
+	preferences: PreferencesStore,
+	style: style::Style,
 
-	results: Vec<Box<dyn SearchResult>>,
+	/** The preferences last applied, so only the parts that changed are applied again. */
+	applied: Shared<Option<Preferences>>,
 
 	pub last_hide: i64
 }
 
 impl Window {
-	pub fn new(gtk: &gtk::Application, styles: &Vec<&'static str>) -> Shared<Self> {
-		let preferences = Preferences::new(None);
+	pub fn new(gtk: &gtk::Application, styles: Vec<String>, preferences: PreferencesStore, visible: bool) -> Shared<Self> {
 		let window = gtk::ApplicationWindow::new(gtk);
 
 		// Basic window configuration //
 
 		window.set_icon_name(Some("system-search"));
-		window.set_default_size(DIMENSIONS.0, DIMENSIONS.1);
 		window.set_decorated(false);
 		window.set_resizable(false);
 		window.set_title("Scout");
 		window.get_style_context().add_class("Scout");
 
-		window.set_skip_taskbar_hint(preferences.borrow().hide_on_unfocus);
-		window.set_skip_pager_hint(preferences.borrow().hide_on_unfocus);
-		window.set_keep_above(preferences.borrow().always_on_top);
-
-		style::style(&window, &preferences.borrow(), styles);
+		let style = style::Style::new(styles);
 
 		let app_container = gtk::Box::new(gtk::Orientation::Vertical, 0);
 		window.add(&app_container);
@@ -78,12 +86,11 @@ impl Window {
 		search_entry.set_icon_from_icon_name(gtk::EntryIconPosition::Primary, Some("search-symbolic"));
 		search_entry.set_widget_name("SearchEntry");
 		search_entry.set_hexpand(true);
-		search_entry.set_size_request(DIMENSIONS.0, 48);
 		top_layout.put(&search_entry, 0, 0);
 
 		let profile = gtk::Button::new();
 		profile.set_widget_name("ProfileButton");
-		top_layout.put(&profile, DIMENSIONS.0 - 41, 7);
+		top_layout.put(&profile, 0, 7);
 
 		let profile_pixbuf = gdk_pixbuf::Pixbuf::from_file_at_scale(
 			&[ "/var/lib/AccountsService/icons/", &whoami::username() ].join(""), 32, 32, true);
@@ -121,9 +128,9 @@ impl Window {
 			dropdown.add(&button);
 		}
 
-		add_dropdown_button(&dropdown_box, "Log Out", 	"system-log-out-symbolic", 	"system.logout");
-		add_dropdown_button(&dropdown_box, "Shut Down", "system-shutdown-symbolic", "system.shutdown");
-		add_dropdown_button(&dropdown_box, "Restart", 	"system-reboot-symbolic", 	"system.restart");
+		for action in Action::ALL.iter() {
+			add_dropdown_button(&dropdown_box, action.get_label(), action.get_icon(), &format!("system.{}", action.get_name()));
+		}
 
 		dropdown_box.pack_start(&gtk::Separator::new(gtk::Orientation::Horizontal), false, false, 3);
 
@@ -135,9 +142,15 @@ impl Window {
 
 		// Result and preview containers //
 
+		let content_overlay = gtk::Overlay::new();
+		app_container.pack_start(&content_overlay, true, true, 0);
+
 		let content_container = gtk::Box::new(gtk::Orientation::Horizontal, 0);
 		content_container.set_widget_name("Content");
-		app_container.pack_start(&content_container, true, true, 0);
+		content_overlay.add(&content_container);
+
+		let toast = toast::Toast::new();
+		content_overlay.add_overlay(toast.get_widget());
 
 		let results_container = gtk::Box::new(gtk::Orientation::Vertical, 0);
 		results_container.set_widget_name("ResultsContainer");
@@ -146,11 +159,22 @@ impl Window {
 		let results_scroller = gtk::ScrolledWindow::new::<gtk::Adjustment, gtk::Adjustment>(None, None);
 		results_scroller.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
 		results_scroller.set_widget_name("ResultsScroller");
-		results_scroller.set_size_request(220, -1);
 		results_container.pack_start(&results_scroller, true, true, 0);
 
-		let results_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
-		results_scroller.add(&results_box);
+		let results = Shared::new(ResultsList::new());
+		results_scroller.add(results.borrow().get_widget());
+
+		let details = gtk::Label::new(None);
+		details.set_widget_name("CompactDetails");
+		details.set_halign(gtk::Align::Start);
+		details.set_ellipsize(pango::EllipsizeMode::End);
+		details.set_no_show_all(true);
+		results_container.pack_start(&details, false, false, 0);
+
+		let results_clone = results.clone();
+		results_scroller.connect_edge_reached(move |_, pos| {
+			if pos == gtk::PositionType::Bottom { results_clone.borrow_mut().show_more(); }
+		});
 
 		let preview = gtk::Box::new(gtk::Orientation::Vertical, 0);
 		preview.set_widget_name("PreviewContainer");
@@ -162,25 +186,35 @@ impl Window {
 
 		// Final configuration //
 
-		if preferences.borrow().opacity != 100 { Window::enable_transparency(&window); }
-		window.show_all();
+		Window::enable_transparency(&window);
+		app_container.show_all();
 
 		// Create the window shared object
 
 		let window = Shared::new(Window {
 			window,
+			header: top_layout,
+			profile,
 			search_entry,
-			results_box,
+			results,
 			results_scroller,
+			details,
+			preview,
 			preview_scroller,
+			toast,
 
 			callbacks: Shared::new(WindowCallbacks::default()),
 			preferences,
-			results: vec![],
+			style,
+			applied: Shared::new(None),
 
 			last_hide: 0
 		});
 
+		let prefs = window.borrow().preferences.get().clone();
+		window.borrow().apply_preferences(&prefs);
+		if visible { window.borrow().window.show(); }
+
 		// Add focus to the first entry when the search entry is selected
 
 		let search_entry_clone = window.borrow().search_entry.clone();
@@ -194,6 +228,27 @@ impl Window {
 			else { results_scroller_clone.get_style_context().remove_class("focus"); }
 		});
 
+		// Show details for the focused result in the compact layout.
+		// This is deferred, as focus changes while the results are being replaced.
+
+		let details_clone = window.borrow().details.clone();
+		let results_clone = window.borrow().results.clone();
+		let preferences_clone = window.borrow().preferences.clone();
+		window.borrow().results.borrow().get_widget().connect_set_focus_child(move |_, row| {
+			let row = row.clone().downcast::<gtk::ListBoxRow>().ok();
+			let details = details_clone.clone();
+			let results = results_clone.clone();
+			let preferences = preferences_clone.clone();
+
+			glib::idle_add_local(move || {
+				if preferences.get().layout != Layout::Compact { return glib::Continue(false); }
+				let results = results.borrow();
+				let res = match &row { Some(row) if row.get_parent().is_some() => results.get_row_result(row), _ => results.find("") };
+				Window::set_details(&details, res);
+				glib::Continue(false)
+			});
+		});
+
 		// Bind search entry functions
 
 		let callbacks_clone = window.borrow().callbacks.clone();
@@ -247,11 +302,7 @@ impl Window {
 
 		let window_clone = window.clone();
 		let preferences_action = gio::SimpleAction::new("preferences", None);
-		preferences_action.connect_activate(move |_, _| {
-			let mut window = window_clone.borrow_mut();
-			PrefsWindow::new(&window.preferences.borrow());
-			window.hide();
-		});
+		preferences_action.connect_activate(move |_, _| window_clone.borrow_mut().show_preferences());
 		actions.add_action(&preferences_action);
 
 		let about_action = gio::SimpleAction::new("about", None);
@@ -261,41 +312,51 @@ impl Window {
 		let actions = gio::SimpleActionGroup::new();
 		window.borrow().window.insert_action_group("system", Some(&actions));
 
-		let logout_action = gio::SimpleAction::new("logout", None);
-		logout_action.connect_activate(move |_, _| drop(std::process::Command::new("xfce4-session-logout").spawn()));
-		actions.add_action(&logout_action);
-
-		let shutdown_action = gio::SimpleAction::new("shutdown", None);
-		shutdown_action.connect_activate(move |_, _| drop(system_shutdown::shutdown()));
-		actions.add_action(&shutdown_action);
-
-		let restart_action = gio::SimpleAction::new("restart", None);
-		restart_action.connect_activate(move |_, _| drop(system_shutdown::reboot()));
-		actions.add_action(&restart_action);
+		for action in Action::ALL.iter().cloned() {
+			let window_clone = window.clone();
+			let system_action = gio::SimpleAction::new(action.get_name(), None);
+			system_action.connect_activate(move |_, _| {
+				let preferences = window_clone.borrow().preferences.clone();
+				window_clone.borrow_mut().hide();
+				Window::run_action(&preferences, action);
+			});
+			actions.add_action(&system_action);
+		}
 
 		// Update hidden state when the app is activated (move this to App)
 
-		let first = Shared::new(true);
+		let first = Shared::new(visible);
 		let window_clone = window.clone();
 		gtk.connect_activate(move |_| {
 			if *first.borrow() {
 				first.replace(false);
 			}
-			else {
-				let mut window = window_clone.borrow_mut();
-				if window.can_show() && !window.is_active() { window.show() }
-				else { window.hide() }
-			}
+			else { window_clone.borrow_mut().toggle(); }
 		});
 
-		if window.borrow().preferences.borrow().hide_on_unfocus {
+		let window_clone = window.clone();
+		window.borrow().window.connect_focus_out_event(move |_, _| {
+			let hide_on_unfocus = window_clone.borrow().preferences.get().hide_on_unfocus;
+			if hide_on_unfocus { window_clone.borrow_mut().hide(); }
+			Inhibit(false)
+		});
+
+		// Regenerate the palette when the GTK theme changes.
+		// This is deferred until the window's style has been updated for the new theme.
+
+		if let Some(settings) = gtk::Settings::get_default() {
 			let window_clone = window.clone();
-			window.borrow().window.connect_focus_out_event(move |_, _| {
-				window_clone.borrow_mut().hide();
-				Inhibit(false)
-			});
+			gtk::SettingsExt::connect_property_gtk_theme_name_notify(&settings, move |_| Window::restyle(&window_clone));
+			let window_clone = window.clone();
+			gtk::SettingsExt::connect_property_gtk_application_prefer_dark_theme_notify(&settings,
+				move |_| Window::restyle(&window_clone));
 		}
 
+		// Apply preference changes as they are made
+
+		let window_clone = window.clone();
+		window.borrow().preferences.connect(move |prefs| window_clone.borrow().apply_preferences(prefs));
+
 		window
 	}
 
@@ -303,32 +364,207 @@ impl Window {
 		self.callbacks.replace(callbacks);
 	}
 
-	pub fn set_results(&mut self, results: Vec<Box<dyn SearchResult>>) {
-		self.results = results;
+	pub fn set_results(&mut self, results: Vec<PluginResult>) {
+		self.preview_scroller.get_children().iter()
+			.for_each(|c| self.preview_scroller.remove(c));
+
+		let compact = self.preferences.get().layout == Layout::Compact;
+		Window::set_details(&self.details, if compact { results.first() } else { None });
+
+		if results.len() > 0 && !compact {
+			self.preview_scroller.add(&profiler::time_widget(&results[0].plugin, || results[0].result.get_preview_widget()));
+			self.preview_scroller.show_all();
+		}
+
+		self.results.borrow_mut().set_results(results);
+	}
 
-		self.results_box.get_children().iter()
-			.for_each(|c| self.results_box.remove(c));
+	/**
+	 * Replaces the results from a single plugin, keeping the others, and ranks them together again.
+	 * Rows for other plugins' results are reused. Results that were dropped by the ranking aren't restored.
+	 */
+
+	pub fn replace_plugin_results(&mut self, plugin: &str, query: &str, results: Vec<PluginResult>, boosts: &Boosts) {
+		let mut all = self.results.borrow_mut().take_results_except(plugin);
+		all.extend(results);
+		self.set_results(PluginParser::rank(all, query, boosts, &RankingConfig::default()));
+	}
+
+	/**
+	 * Returns the shown results, in order.
+	 */
+
+	pub fn get_shown_results(&self) -> std::cell::Ref<[PluginResult]> {
+		std::cell::Ref::map(self.results.borrow(), |results| results.get_results())
+	}
+
+	/**
+	 * Removes all results and their widgets.
+	 */
+
+	pub fn clear_results(&mut self) {
 		self.preview_scroller.get_children().iter()
 			.for_each(|c| self.preview_scroller.remove(c));
+		Window::set_details(&self.details, None);
+		self.results.borrow_mut().clear();
+	}
+
+	/**
+	 * Activates a displayed result by its identifier, or the first result if the identifier is empty,
+	 * and hides the window. Returns false if no result was found.
+	 */
+
+	pub fn activate_result(&mut self, id: &str) -> bool {
+		let found = match self.results.borrow().find(id) {
+			Some(res) => {
+				res.result.activate();
+				(self.callbacks.borrow_mut().on_activate)(&res.plugin, &res.result.get_id());
+				true
+			},
+			None => false
+		};
+
+		if found { self.hide(); }
+		found
+	}
 
-		if self.results.len() > 0 {
-			self.preview_scroller.add(&self.results[0].get_preview_widget());
+	/**
+	 * Updates the window's hints, geometry, and stylesheet to match the preferences provided.
+	 * The window is only resized and placed if its geometry changed, and the stylesheet is only
+	 * regenerated if the opacity or theme changed, as this is called as preference sliders are dragged.
+	 */
 
-			for (i, res) in self.results.iter().enumerate() {
-				res.set_first(i == 0);
-				self.results_box.pack_start(&res.get_result_widget(), false, false, 0);
-			}
+	fn apply_preferences(&self, prefs: &Preferences) {
+		let last = self.applied.replace(Some(prefs.clone()));
 
-			self.results_box.show_all();
-			self.preview_scroller.show_all();
+		let hints = |p: &Preferences| (p.hide_on_unfocus, p.always_on_top);
+		let geometry = |p: &Preferences| (p.width, p.height, p.results_width, p.anchor, p.monitor, p.layout);
+		let style = |p: &Preferences| (p.opacity, p.theme.clone());
+
+		if last.as_ref().map_or(true, |last| hints(last) != hints(prefs)) {
+			self.window.set_skip_taskbar_hint(prefs.hide_on_unfocus);
+			self.window.set_skip_pager_hint(prefs.hide_on_unfocus);
+			self.window.set_keep_above(prefs.always_on_top);
+		}
+
+		if last.as_ref().map_or(true, |last| geometry(last) != geometry(prefs)) { self.apply_geometry(prefs); }
+		// Unchanged preferences are only notified by `PreferencesStore::refresh`, to reload the theme file.
+		let refreshed = last.as_ref() == Some(prefs);
+		if refreshed || last.as_ref().map_or(true, |last| style(last) != style(prefs)) { self.style.update(&self.window, prefs); }
+	}
+
+	/**
+	 * Sizes the window and its panes for the layout, and places it on its monitor.
+	 */
+
+	fn apply_geometry(&self, prefs: &Preferences) {
+
+		// In the compact layout, the window is only as tall as its results, up to the configured height.
+
+		let compact = prefs.layout == Layout::Compact;
+		if compact { self.window.get_style_context().add_class("compact"); }
+		else { self.window.get_style_context().remove_class("compact"); }
+
+		self.preview.set_visible(!compact);
+		if !compact { Window::set_details(&self.details, None); }
+
+		self.results_scroller.set_propagate_natural_height(compact);
+		self.results_scroller.set_max_content_height(if compact { prefs.height - 48 } else { -1 });
+		self.results_scroller.set_size_request(if compact { prefs.width } else { prefs.results_width }, -1);
+
+		self.window.set_size_request(prefs.width, if compact { -1 } else { prefs.height });
+		self.window.resize(prefs.width, if compact { 1 } else { prefs.height });
+		self.search_entry.set_size_request(prefs.width, 48);
+		self.header.move_(&self.profile, prefs.width - 41, 7);
+		self.place(prefs);
+	}
+
+	/**
+	 * Shows the title and subtitle of a result below the results, or hides the details if there is no result.
+	 */
+
+	fn set_details(details: &gtk::Label, res: Option<&PluginResult>) {
+		match res {
+			Some(res) => {
+				details.set_markup(&format!("<b>{}</b>  {}",
+					glib::markup_escape_text(&res.result.get_title()), glib::markup_escape_text(&res.result.get_subtitle())));
+				details.show();
+			},
+			None => details.hide()
 		}
 	}
 
+	/**
+	 * Moves the window to its anchor on the monitor specified by the preferences.
+	 * When anchored to the top third, the space above the window is half the space below it.
+	 */
+
+	fn place(&self, prefs: &Preferences) {
+		let display = self.window.get_display();
+
+		let monitor = match prefs.monitor {
+			Monitor::Primary => display.get_primary_monitor(),
+			Monitor::Pointer => display.get_default_seat().and_then(|seat| seat.get_pointer()).and_then(|pointer| {
+				let (_, x, y) = pointer.get_position();
+				display.get_monitor_at_point(x, y)
+			})
+		}.or_else(|| display.get_monitor(0));
+
+		let area = match monitor { Some(monitor) => monitor.get_workarea(), None => return };
+
+		let x = area.x + (area.width - prefs.width) / 2;
+		let y = match prefs.anchor {
+			Anchor::Center => area.y + (area.height - prefs.height) / 2,
+			Anchor::TopThird => area.y + (area.height - prefs.height) / 3
+		};
+
+		self.window.move_(x, y);
+	}
+
+	/**
+	 * Opens the preferences window, hiding the search window.
+	 */
+
+	pub fn show_preferences(&mut self) {
+		PrefsWindow::new(&self.preferences);
+		self.hide();
+	}
+
+	/**
+	 * Runs a session action, asking for confirmation first if it ends the session.
+	 */
+
+	pub fn run_action(preferences: &PreferencesStore, action: Action) {
+		let commands = preferences.get().session_commands.clone();
+		let run = move || {
+			if let Err(err) = Session::new(&commands).run(action) {
+				log::error!("Failed to {}: {}", action.get_label().to_lowercase(), err);
+			}
+		};
+
+		if action.needs_confirmation() { confirm::confirm_action(action, run); }
+		else { run(); }
+	}
+
+	/**
+	 * Regenerates the window's stylesheet once the main loop is idle.
+	 */
+
+	fn restyle(window: &Shared<Window>) {
+		let window = window.clone();
+		glib::idle_add_local(move || {
+			let window = window.borrow();
+			window.style.invalidate_palette();
+			window.style.update(&window.window, &window.preferences.get());
+			glib::Continue(false)
+		});
+	}
+
 	fn enable_transparency(window: &gtk::ApplicationWindow) {
 		fn set_visual(window: &gtk::ApplicationWindow, _: Option<&gdk::Screen>) {
 			let screen = window.get_screen().unwrap();
 			if let Some(ref visual) = screen.get_rgba_visual() { window.set_visual(Some(visual)); }
-			else { println!("RGBA missing."); }
+			else { log::warn!("The screen doesn't support transparency."); }
 		}
 
 		fn draw(_: &gtk::ApplicationWindow, ctx: &cairo::Context) -> Inhibit {
@@ -348,26 +584,80 @@ impl Window {
 		glib::get_monotonic_time() - self.last_hide >= 250_000
 	}
 
+	/**
+	 * Shows a notification as a toast at the bottom of the window.
+	 */
+
+	pub fn show_notification(&self, notification: &Notification) {
+		self.toast.show(notification);
+	}
+
+	/**
+	 * Hides the notification being shown, and drops its action.
+	 */
+
+	pub fn hide_notification(&self) {
+		self.toast.hide();
+	}
+
+	/**
+	 * Replaces the plugins' stylesheets, and regenerates the window's style.
+	 */
+
+	pub fn set_styles(&mut self, styles: Vec<String>) {
+		self.style.set_styles(styles);
+		self.style.update(&self.window, &self.preferences.get());
+	}
+
+	pub fn is_visible(&self) -> bool {
+		self.window.is_visible()
+	}
+
 	fn is_active(&self) -> bool {
 		self.window.get_focus().is_some() && self.window.is_visible()
 	}
 
-	fn show(&mut self) {
+	pub fn show(&mut self) {
 		if !self.can_show() { return }
 
+		self.place(&self.preferences.get());
 		self.window.show();
 		self.search_entry.grab_focus();
+		(self.callbacks.borrow_mut().on_show)();
 	}
 
-	fn hide(&mut self) {
+	pub fn hide(&mut self) {
 		if !self.window.is_visible() { return }
 
 		self.window.hide();
+		self.toast.hide();
 		self.last_hide = glib::get_monotonic_time();
 
 		let search = self.search_entry.clone();
-		drop(self);
-
 		search.set_text("");
+		search.set_placeholder_text(None);
+		(self.callbacks.borrow_mut().on_hide)();
+	}
+
+	pub fn toggle(&mut self) {
+		if self.can_show() && !self.is_active() { self.show() }
+		else { self.hide() }
+	}
+
+	/**
+	 * Replaces the search entry's text, placing the cursor at the end.
+	 */
+
+	pub fn set_query(&mut self, query: &str) {
+		self.search_entry.set_text(query);
+		self.search_entry.set_position(-1);
+	}
+
+	/**
+	 * Indicates that searches are limited to a single plugin.
+	 */
+
+	pub fn set_scope(&mut self, plugin: Option<&str>) {
+		self.search_entry.set_placeholder_text(plugin.map(|plugin| format!("Search {}", plugin)).as_deref());
 	}
 }
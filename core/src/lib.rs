@@ -11,7 +11,7 @@
  *
  * Now, define your plugin struct in lib.rs. Make sure you implement the Plugin trait in this crate.
  *
- * ```rust
+ * ```rust,ignore
  * pub struct MyPlugin;
  *
  * impl scout_core::Plugin for MyPlugin {
@@ -21,20 +21,35 @@
  *
  * Finally, add the following code to the bottom of your lib.rs to export the plugin.
  *
- * ```rust
+ * ```rust,ignore
+ * use scout_core::{ Shared, PluginBindings };
+ *
  * #[allow(improper_ctypes_definitions)]
- * extern "C" fn register(registrar: &mut dyn scout_core::PluginRegistrar) {
- *   registrar.register("my_plugin", Box::new(MyPlugin));
+ * extern "C" fn register(bindings: Shared<Box<dyn PluginBindings>>) {
+ *   let mut bindings = bindings.borrow_mut();
+ *   bindings.register("my_plugin", Box::new(MyPlugin));
+ *   bindings.get_logger().install();
  * }
  *
  * scout_core::export_plugin!(register);
  * ```
  *
  * That's it! Now your plugin can be built using `cargo build --all`, and the resultant .so can be loaded by Scout.
+ * Scout loads libraries named `libscout_plugin_*.so`, so name your crate `scout_plugin_<name>`.
+ * They are loaded from the directory containing Scout's executable, `$XDG_DATA_HOME/scout/plugins`, and `/usr/lib/scout/plugins`.
+ *
+ * Plugins are queried from worker threads, so their data must be `Send + Sync`.
+ * Data that is changed after registering, such as an index built with `Tasks`, can be kept in a `SyncShared`.
+ * `Shared` is not thread-safe, and should only hold values used on the main thread, like GTK widgets.
  */
 
+use std::sync::Arc;
+
 mod shared;
-pub use shared::Shared;
+pub use shared::{ Shared, WeakShared };
+
+mod sync_shared;
+pub use sync_shared::{ SyncShared, WeakSyncShared, TryBorrowError };
 
 mod plugin;
 pub use plugin::*;
@@ -42,8 +57,33 @@ pub use plugin::*;
 mod searchresult;
 pub use searchresult::SearchResult;
 
+mod logger;
+pub use logger::PluginLogger;
+
+mod storage;
+pub use storage::PluginStorage;
+
+mod notify;
+pub use notify::{ Notifier, Notification, NotifyLevel, NotifyAction };
+
+mod update;
+pub use update::{ Updater, ResultUpdate };
+
+mod tasks;
+pub use tasks::{ Tasks, TaskRunner, TimerId };
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+/** The `log` crate, re-exported so plugins log through the same version as Scout. */
+pub use log;
+
+
+/**
+ * The current core scout_core version. Plugins built against a different version are not loaded,
+ * so this must be bumped whenever the `Plugin`, `SearchResult` or `PluginBindings` traits change.
+ */
 
-/** The current core scout_core version. */
 pub static CORE_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /** The current Rust compiler version. */
@@ -65,7 +105,44 @@ pub trait PluginBindings {
 
 	fn add_stylesheet(&mut self, stylesheet: &'static str);
 
-	fn get_plugin(&self) -> Result<(&str, Shared<Box<dyn Plugin>>)>;
+	/** Returns a logger that tags messages with the plugin's identifier. Call this after `register`. */
+	fn get_logger(&self) -> PluginLogger;
+
+	/**
+	 * Returns the plugin's persistent storage, which can be kept and used from any thread. Call this after `register`.
+	 * Storage is kept in `$XDG_DATA_HOME/scout/plugins/<identifier>/`, with a cache directory in `$XDG_CACHE_HOME`.
+	 * Every call returns a handle to the same storage.
+	 */
+
+	fn get_storage(&self) -> PluginStorage;
+
+	/**
+	 * Returns a notifier for showing messages to the user, which can be kept and used from any thread.
+	 * Call this after `register`.
+	 */
+
+	fn get_notifier(&self) -> Notifier;
+
+	/**
+	 * Returns an updater for replacing the plugin's shown results, which can be kept and used from any thread.
+	 * Call this after `register`.
+	 */
+
+	fn get_updater(&self) -> Updater;
+
+	/**
+	 * Returns a handle for running work in the background and on the main thread,
+	 * which can be kept and used from any thread.
+	 */
+
+	fn get_tasks(&self) -> Tasks;
+
+	/** Shows a message to the user, such as "Copied to clipboard". */
+	fn notify(&self, level: NotifyLevel, message: &str, action: Option<NotifyAction>) {
+		self.get_notifier().notify(level, message, action);
+	}
+
+	fn get_plugin(&self) -> Result<(&str, Arc<dyn Plugin>)>;
 }
 
 /** Exports a plugin for Scout to use. See the module documentation for usage details. */
@@ -0,0 +1,51 @@
+/**! Routes a plugin's log messages to Scout's log. */
+
+use std::sync::Arc;
+
+/**
+ * A logger given to a plugin by its bindings, which tags each message with the plugin's identifier.
+ * Plugins are loaded as separate libraries with their own copy of the `log` crate,
+ * so `install` must be called for the `log` macros in a plugin to reach Scout's log.
+ */
+
+#[derive(Clone)]
+pub struct PluginLogger {
+	target: String,
+	inner: Arc<dyn log::Log>
+}
+
+impl PluginLogger {
+	pub fn new(identifier: &str, inner: Arc<dyn log::Log>) -> Self {
+		PluginLogger { target: [ "plugin:", identifier ].join(""), inner }
+	}
+
+	/**
+	 * Installs the logger for the plugin's library, so that its `log` macros write to Scout's log.
+	 * This does nothing if a logger has already been installed.
+	 */
+
+	pub fn install(self) {
+		if log::set_boxed_logger(Box::new(self)).is_ok() { log::set_max_level(log::LevelFilter::Trace); }
+	}
+}
+
+impl log::Log for PluginLogger {
+	fn enabled(&self, metadata: &log::Metadata) -> bool {
+		self.inner.enabled(&log::Metadata::builder().level(metadata.level()).target(&self.target).build())
+	}
+
+	fn log(&self, record: &log::Record) {
+		self.inner.log(&log::Record::builder()
+			.args(*record.args())
+			.level(record.level())
+			.target(&self.target)
+			.module_path(record.module_path())
+			.file(record.file())
+			.line(record.line())
+			.build());
+	}
+
+	fn flush(&self) {
+		self.inner.flush();
+	}
+}
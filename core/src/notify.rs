@@ -0,0 +1,75 @@
+/**! Notifications that plugins can show to the user. */
+
+use std::sync::Arc;
+
+/**
+ * How important a notification is, which determines how it is styled.
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyLevel {
+	Info,
+	Warning,
+	Error
+}
+
+/**
+ * A button shown with a notification, which calls its callback on the main thread when clicked.
+ */
+
+#[derive(Clone)]
+pub struct NotifyAction {
+	pub label: String,
+	pub callback: Arc<dyn Fn() + Send + Sync>
+}
+
+impl NotifyAction {
+	pub fn new<F: Fn() + Send + Sync + 'static>(label: &str, callback: F) -> Self {
+		NotifyAction { label: label.to_owned(), callback: Arc::new(callback) }
+	}
+}
+
+impl std::fmt::Debug for NotifyAction {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.debug_struct("NotifyAction").field("label", &self.label).finish()
+	}
+}
+
+/**
+ * A message to show to the user.
+ */
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+
+	/** The identifier of the plugin that sent the notification. */
+	pub source: String,
+	pub level: NotifyLevel,
+	pub message: String,
+	pub action: Option<NotifyAction>
+}
+
+/**
+ * Sends notifications to the app. This is provided by a plugin's bindings,
+ * and can be cloned cheaply and used from any thread, such as in search results.
+ */
+
+#[derive(Clone)]
+pub struct Notifier {
+	source: String,
+	send: Arc<dyn Fn(Notification) + Send + Sync>
+}
+
+impl Notifier {
+	pub fn new<F: Fn(Notification) + Send + Sync + 'static>(source: &str, send: F) -> Self {
+		Notifier { source: source.to_owned(), send: Arc::new(send) }
+	}
+
+	/**
+	 * Shows a notification in the window, or as a desktop notification if the window is hidden.
+	 */
+
+	pub fn notify(&self, level: NotifyLevel, message: &str, action: Option<NotifyAction>) {
+		(self.send)(Notification { source: self.source.clone(), level, message: message.to_owned(), action });
+	}
+}
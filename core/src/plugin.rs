@@ -33,9 +33,11 @@ pub type Result<T> = std::result::Result<T, InvocationError>;
 
 /**
  * Base trait that all Plugins should implement.
+ * Plugins are queried from a worker thread, so they must be `Send + Sync`,
+ * and should not hold GTK widgets.
  */
 
-pub trait Plugin {
+pub trait Plugin: Send + Sync {
 
 	/**
 	 * Returns a list of probable results for the inputted query,
@@ -1,9 +1,11 @@
 
 /**
  * Base search result trait.
+ * Results are created on a worker thread and sent to the main thread,
+ * so they should only store data. Widgets are created on the main thread when requested.
  */
 
-pub trait SearchResult {
+pub trait SearchResult: Send {
 
 	/**
 	 * Returns the score of the result, as determined by the plugin.
@@ -13,14 +15,45 @@ pub trait SearchResult {
 	fn get_score(&self) -> usize;
 
 
+	/**
+	 * Returns an identifier for the result, which should be the same across queries.
+	 * The app uses this to reuse widgets for results that are shown again.
+	 */
+
+	fn get_id(&self) -> String;
+
+
+	/**
+	 * Returns the result's title, as it should be displayed to the user.
+	 */
+
+	fn get_title(&self) -> String;
+
+
+	/**
+	 * Returns a short description of the result, which may be empty.
+	 */
+
+	fn get_subtitle(&self) -> String;
+
+
+	/**
+	 * Returns the names of the secondary actions the result provides.
+	 */
+
+	fn get_actions(&self) -> Vec<String>;
+
+
 	/**
 	 * Indicates that this result is the first result displayed,
 	 * which may trigger special focus / display behavior.
 	 * The first result's primary button should not be focusable,
 	 * it will instead be triggered with the `activate` method.
+	 *
+	 * - `widget` - A widget previously returned by `get_result_widget`.
 	 */
 
-	fn set_first(&self, first: bool) -> ();
+	fn set_first(&self, widget: &gtk::Widget, first: bool) -> ();
 
 
 	/**
@@ -32,14 +65,14 @@ pub trait SearchResult {
 
 
 	/**
-	 * Returns a widget representing the result in the results pane.
+	 * Creates a widget representing the result in the results pane.
 	 */
 
 	fn get_result_widget(&self) -> gtk::Widget;
 
 
 	/**
-	 * Returns a widget representing the result in the preview pane.
+	 * Creates a widget representing the result in the preview pane.
 	 */
 
 	fn get_preview_widget(&self) -> gtk::Widget;
@@ -6,7 +6,7 @@
 use std::fmt;
 use std::ops::Deref;
 use std::rc::{ Rc, Weak };
-use std::cell::{ RefCell, Ref, RefMut, BorrowMutError };
+use std::cell::{ RefCell, Ref, RefMut, BorrowError, BorrowMutError };
 
 /**
  * Represents a shared pointer to an object
@@ -63,9 +63,9 @@ impl <T> Shared<T> {
 	 * Borrows an immutable reference to the stored object, if there are no mutable borrows in use.
 	 */
 
-	// pub fn try_borrow(&self) -> Result<Ref<T>, BorrowError> {
-	// 	self.v.try_borrow()
-	// }
+	pub fn try_borrow(&self) -> Result<Ref<T>, BorrowError> {
+		self.v.try_borrow()
+	}
 
 
 	/**
@@ -89,9 +89,9 @@ impl <T> Shared<T> {
 	 * Borrows a mutable pointer to the stored object.
 	 */
 
-	// pub fn as_ptr(&self) -> *mut T {
-	// 	self.v.as_ptr()
-	// }
+	pub fn as_ptr(&self) -> *mut T {
+		self.v.as_ptr()
+	}
 
 
 	/**
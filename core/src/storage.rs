@@ -0,0 +1,245 @@
+/**! Persistent key/value storage for plugins, kept in a directory of the plugin's own. */
+
+use std::io::Write;
+use std::sync::{ Arc, Mutex };
+use std::path::{ Path, PathBuf };
+use serde::{ Serialize, de::DeserializeOwned };
+
+/** The file in a plugin's data directory that values are stored in. */
+static VALUES_FILE: &str = "storage.json";
+
+struct StorageState {
+	values: Option<serde_json::Map<String, serde_json::Value>>,
+
+	/** Whether the quota warning has been logged, so it is only logged once until usage drops below the quota. */
+	warned: bool
+}
+
+/**
+ * A plugin's persistent storage, provided by its bindings.
+ * Values are serialized as JSON, and written to disk as soon as they are set.
+ * The storage can be cloned cheaply and used from any thread.
+ */
+
+#[derive(Clone)]
+pub struct PluginStorage {
+	dir: PathBuf,
+	cache_dir: PathBuf,
+
+	/** How many bytes the data directory may use before a warning is logged. */
+	quota: u64,
+	state: Arc<Mutex<StorageState>>
+}
+
+impl PluginStorage {
+	pub fn new(dir: PathBuf, cache_dir: PathBuf, quota: u64) -> Self {
+		PluginStorage { dir, cache_dir, quota, state: Arc::new(Mutex::new(StorageState { values: None, warned: false })) }
+	}
+
+	/**
+	 * Returns the plugin's data directory, which plugins may also store their own files in.
+	 */
+
+	pub fn get_dir(&self) -> &Path {
+		&self.dir
+	}
+
+	/**
+	 * Returns the plugin's cache directory, for data that can be recreated, creating it if it doesn't exist.
+	 * The cache directory doesn't count towards the quota.
+	 */
+
+	pub fn get_cache_dir(&self) -> std::io::Result<PathBuf> {
+		std::fs::create_dir_all(&self.cache_dir)?;
+		Ok(self.cache_dir.clone())
+	}
+
+	/**
+	 * Returns a stored value, or None if there isn't one or it can't be deserialized as the type requested.
+	 */
+
+	pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+		let mut state = self.state.lock().unwrap();
+		let value = self.load(&mut state).get(key)?.clone();
+		serde_json::from_value(value).ok()
+	}
+
+	/**
+	 * Stores a value, replacing any stored with the same key.
+	 */
+
+	pub fn set<T: Serialize>(&self, key: &str, value: &T) -> std::io::Result<()> {
+		let value = serde_json::to_value(value).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+		let mut state = self.state.lock().unwrap();
+		self.load(&mut state).insert(key.to_owned(), value);
+		self.save(&mut state)
+	}
+
+	/**
+	 * Removes a stored value, returning whether there was one.
+	 */
+
+	pub fn remove(&self, key: &str) -> std::io::Result<bool> {
+		let mut state = self.state.lock().unwrap();
+		if self.load(&mut state).remove(key).is_none() { return Ok(false); }
+		self.save(&mut state).map(|_| true)
+	}
+
+	/**
+	 * Returns the keys of every stored value.
+	 */
+
+	pub fn get_keys(&self) -> Vec<String> {
+		let mut state = self.state.lock().unwrap();
+		self.load(&mut state).keys().cloned().collect()
+	}
+
+	/**
+	 * Returns the stored values, reading them from disk the first time they are needed.
+	 * Values that can't be read are discarded, with a warning.
+	 */
+
+	fn load<'a>(&self, state: &'a mut StorageState) -> &'a mut serde_json::Map<String, serde_json::Value> {
+		let path = self.dir.join(VALUES_FILE);
+		state.values.get_or_insert_with(|| match std::fs::read_to_string(&path) {
+			Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|err| {
+				log::warn!("Error reading plugin storage {:?}, starting over. {}", path, err);
+				serde_json::Map::new()
+			}),
+			Err(_) => serde_json::Map::new()
+		})
+	}
+
+	/**
+	 * Writes the values to disk atomically, and warns if the data directory has grown past the quota.
+	 */
+
+	fn save(&self, state: &mut StorageState) -> std::io::Result<()> {
+		std::fs::create_dir_all(&self.dir)?;
+
+		let contents = serde_json::to_vec(state.values.as_ref().unwrap_or(&serde_json::Map::new()))
+			.map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+
+		let temp = self.dir.join([ VALUES_FILE, ".tmp" ].join(""));
+		let mut file = std::fs::File::create(&temp)?;
+		file.write_all(&contents)?;
+		file.sync_all()?;
+		drop(file);
+		std::fs::rename(&temp, self.dir.join(VALUES_FILE))?;
+
+		let size = get_size(&self.dir);
+		if size > self.quota && !state.warned {
+			log::warn!("Plugin storage {:?} is using {} KiB, more than its quota of {} KiB.", self.dir, size / 1024, self.quota / 1024);
+		}
+		state.warned = size > self.quota;
+		Ok(())
+	}
+}
+
+/**
+ * Returns the total size of the files in a directory and its subdirectories, in bytes.
+ */
+
+fn get_size(dir: &Path) -> u64 {
+	std::fs::read_dir(dir).map(|entries| entries.filter_map(|entry| entry.ok()).map(|entry| {
+		match entry.metadata() {
+			Ok(metadata) if metadata.is_dir() => get_size(&entry.path()),
+			Ok(metadata) => metadata.len(),
+			Err(_) => 0
+		}
+	}).sum()).unwrap_or(0)
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::path::{ Path, PathBuf };
+	use super::{ PluginStorage, VALUES_FILE };
+
+	/**
+	 * Returns an empty temporary directory for a test's storage.
+	 */
+
+	fn temp_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("scout-storage-test-{}-{}", std::process::id(), name));
+		drop(std::fs::remove_dir_all(&dir));
+		dir
+	}
+
+	fn storage(dir: &Path, quota: u64) -> PluginStorage {
+		PluginStorage::new(dir.join("data"), dir.join("cache"), quota)
+	}
+
+	fn is_over_quota(storage: &PluginStorage) -> bool {
+		storage.state.lock().unwrap().warned
+	}
+
+	#[test]
+	fn gets_sets_and_removes_values() {
+		let storage = storage(&temp_dir("values"), u64::MAX);
+		assert_eq!(storage.get::<u32>("count"), None);
+
+		storage.set("count", &3).unwrap();
+		storage.set("names", &vec![ "a", "b" ]).unwrap();
+		assert_eq!(storage.get::<u32>("count"), Some(3));
+		assert_eq!(storage.get::<Vec<String>>("names"), Some(vec![ "a".to_owned(), "b".to_owned() ]));
+		assert_eq!(storage.get::<String>("count"), None);
+
+		let mut keys = storage.get_keys();
+		keys.sort();
+		assert_eq!(keys, vec![ "count", "names" ]);
+
+		assert!(storage.remove("count").unwrap());
+		assert!(!storage.remove("count").unwrap());
+		assert_eq!(storage.get::<u32>("count"), None);
+	}
+
+	#[test]
+	fn shares_values_between_clones() {
+		let storage = storage(&temp_dir("clones"), u64::MAX);
+		let clone = storage.clone();
+
+		storage.set("count", &3).unwrap();
+		assert_eq!(clone.get::<u32>("count"), Some(3));
+	}
+
+	#[test]
+	fn persists_values_between_instances() {
+		let dir = temp_dir("persist");
+		storage(&dir, u64::MAX).set("count", &3).unwrap();
+
+		assert_eq!(storage(&dir, u64::MAX).get::<u32>("count"), Some(3));
+		assert!(dir.join("data").join(VALUES_FILE).exists());
+		assert!(!dir.join("data").join([ VALUES_FILE, ".tmp" ].join("")).exists());
+	}
+
+	#[test]
+	fn discards_unreadable_values() {
+		let dir = temp_dir("unreadable");
+		std::fs::create_dir_all(dir.join("data")).unwrap();
+		std::fs::write(dir.join("data").join(VALUES_FILE), "{ \"count\": ").unwrap();
+
+		let first = storage(&dir, u64::MAX);
+		assert!(first.get_keys().is_empty());
+		first.set("count", &3).unwrap();
+		assert_eq!(storage(&dir, u64::MAX).get::<u32>("count"), Some(3));
+	}
+
+	#[test]
+	fn warns_when_over_quota() {
+		let storage = storage(&temp_dir("quota"), 256);
+
+		storage.set("small", &1).unwrap();
+		assert!(!is_over_quota(&storage));
+
+		storage.set("large", &"x".repeat(512)).unwrap();
+		assert!(is_over_quota(&storage));
+
+		// Files outside the cache directory count towards the quota, files in it don't.
+		storage.remove("large").unwrap();
+		assert!(!is_over_quota(&storage));
+		std::fs::write(storage.get_cache_dir().unwrap().join("index"), "x".repeat(512)).unwrap();
+		storage.set("small", &2).unwrap();
+		assert!(!is_over_quota(&storage));
+	}
+}
@@ -0,0 +1,301 @@
+/*!
+ * A thread-safe counterpart to Shared, over the Arc<RwLock<T>> pattern.
+ * Use this for plugin data that is used from worker threads, such as an index built in the background.
+ * GTK widgets are not thread-safe, and should be kept in a Shared on the main thread instead.
+ */
+
+use std::fmt;
+use std::sync::{ Arc, Weak, RwLock, RwLockReadGuard, RwLockWriteGuard, PoisonError, TryLockError };
+
+/**
+ * Represents a shared pointer to an object
+ * on the heap, with interior mutability, which can be sent between threads.
+ *
+ * If a thread panics while holding a mutable borrow, the object is poisoned, and stays poisoned.
+ * `borrow`, `borrow_mut` and `replace` ignore poisoning, and return the object as the panicking thread left it,
+ * so that a failed background task does not break the plugin's other uses of it. The object may have been left
+ * partially modified, so `try_borrow` and `try_borrow_mut` report poisoning, along with the borrow,
+ * for callers that need to check or repair the object. `is_poisoned` also reports it.
+ */
+
+pub struct SyncShared<T> {
+	v: Arc<RwLock<T>>
+}
+
+impl <T> SyncShared<T> {
+
+	/**
+	 * Creates a new SyncShared with the contents provided.
+	 */
+
+	pub fn new(t: T) -> SyncShared<T> {
+		SyncShared { v: Arc::new(RwLock::new(t)) }
+	}
+
+
+	/**
+	 * Borrows an immutable reference to the stored object, blocking while it is mutably borrowed.
+	 */
+
+	pub fn borrow(&self) -> RwLockReadGuard<T> {
+		self.v.read().unwrap_or_else(PoisonError::into_inner)
+	}
+
+
+	/**
+	 * Borrows an immutable reference to the stored object, if there are no mutable borrows in use.
+	 * If the object is poisoned, the borrow is returned in the error.
+	 */
+
+	pub fn try_borrow(&self) -> Result<RwLockReadGuard<T>, TryBorrowError<RwLockReadGuard<T>>> {
+		self.v.try_read().map_err(TryBorrowError::from)
+	}
+
+
+	/**
+	 * Borrows a mutable reference to the stored object, blocking while it is borrowed elsewhere.
+	 */
+
+	pub fn borrow_mut(&self) -> RwLockWriteGuard<T> {
+		self.v.write().unwrap_or_else(PoisonError::into_inner)
+	}
+
+
+	/**
+	 * Borrows a mutable reference to the stored object, if there are no other borrows in use.
+	 * If the object is poisoned, the borrow is returned in the error.
+	 */
+
+	pub fn try_borrow_mut(&self) -> Result<RwLockWriteGuard<T>, TryBorrowError<RwLockWriteGuard<T>>> {
+		self.v.try_write().map_err(TryBorrowError::from)
+	}
+
+
+	/**
+	 * Returns whether a thread panicked while mutably borrowing the object.
+	 */
+
+	pub fn is_poisoned(&self) -> bool {
+		self.v.is_poisoned()
+	}
+
+
+	/**
+	 * Gets a weak reference to this shared object.
+	 */
+
+	pub fn get_weak(&self) -> WeakSyncShared<T> {
+		WeakSyncShared::new(&self.v)
+	}
+
+
+	/**
+	 * Creates a shared from a weak reference.
+	 */
+
+	pub fn from_weak(weak: &WeakSyncShared<T>) -> Result<SyncShared<T>, ()> {
+		weak.v.upgrade().map(|v| SyncShared { v }).ok_or(())
+	}
+
+
+	/**
+	 * Replaces the stored object with a new one.
+	 */
+
+	pub fn replace(&self, t: T) -> T {
+		std::mem::replace(&mut *self.borrow_mut(), t)
+	}
+}
+
+
+impl <T> Clone for SyncShared<T> {
+
+	/**
+	 * Creates a new pointer to the stored memory.
+	 * This operation is inexpensive, and does not clone the underlying object.
+	 */
+
+	fn clone(&self) -> Self {
+		SyncShared { v: Arc::clone(&self.v) }
+	}
+}
+
+
+impl <T: Default> Default for SyncShared<T> {
+	fn default() -> Self {
+		SyncShared::new(T::default())
+	}
+}
+
+
+impl <T: fmt::Display> fmt::Display for SyncShared<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", &*self.borrow())
+	}
+}
+
+
+impl <T: fmt::Debug> fmt::Debug for SyncShared<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:?}", &*self.borrow())
+	}
+}
+
+/**
+ * The reason `try_borrow` or `try_borrow_mut` didn't return a borrow normally.
+ */
+
+pub enum TryBorrowError<G> {
+
+	/** The object is borrowed elsewhere, and borrowing it would have blocked. */
+	WouldBlock,
+
+	/** A thread panicked while mutably borrowing the object. The borrow can still be used. */
+	Poisoned(G)
+}
+
+impl <G> From<TryLockError<G>> for TryBorrowError<G> {
+	fn from(err: TryLockError<G>) -> Self {
+		match err {
+			TryLockError::WouldBlock => TryBorrowError::WouldBlock,
+			TryLockError::Poisoned(err) => TryBorrowError::Poisoned(err.into_inner())
+		}
+	}
+}
+
+impl <G> fmt::Debug for TryBorrowError<G> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			TryBorrowError::WouldBlock => write!(f, "WouldBlock"),
+			TryBorrowError::Poisoned(_) => write!(f, "Poisoned(..)")
+		}
+	}
+}
+
+impl <G> fmt::Display for TryBorrowError<G> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			TryBorrowError::WouldBlock => write!(f, "the object is already borrowed"),
+			TryBorrowError::Poisoned(_) => write!(f, "a thread panicked while borrowing the object")
+		}
+	}
+}
+
+/**
+ * Represents a weak pointer to a thread-safe shared pointer.
+ */
+
+pub struct WeakSyncShared<T> {
+	v: Weak<RwLock<T>>
+}
+
+impl <T> WeakSyncShared<T> {
+
+	pub fn new(v: &Arc<RwLock<T>>) -> Self {
+		WeakSyncShared {
+			v: Arc::downgrade(v)
+		}
+	}
+
+	pub fn to_shared(&self) -> Result<SyncShared<T>, ()> {
+		SyncShared::from_weak(&self)
+	}
+}
+
+impl <T> Clone for WeakSyncShared<T> {
+	fn clone(&self) -> Self {
+		WeakSyncShared { v: self.v.clone() }
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::sync::mpsc;
+	use super::{ SyncShared, TryBorrowError };
+
+	#[test]
+	fn borrows_and_replaces() {
+		let shared = SyncShared::new(1);
+		assert_eq!(*shared.borrow(), 1);
+
+		*shared.borrow_mut() = 2;
+		assert_eq!(shared.replace(3), 2);
+		assert_eq!(*shared.clone().borrow(), 3);
+	}
+
+	#[test]
+	fn try_borrow_would_block_while_mutably_borrowed() {
+		let shared = SyncShared::new(1);
+
+		let read = shared.borrow();
+		assert!(shared.try_borrow().is_ok());
+		assert!(matches!(shared.try_borrow_mut(), Err(TryBorrowError::WouldBlock)));
+		drop(read);
+
+		let write = shared.borrow_mut();
+		assert!(matches!(shared.try_borrow(), Err(TryBorrowError::WouldBlock)));
+		assert!(matches!(shared.try_borrow_mut(), Err(TryBorrowError::WouldBlock)));
+		drop(write);
+
+		assert!(shared.try_borrow_mut().is_ok());
+	}
+
+	#[test]
+	fn try_borrow_would_block_while_borrowed_on_another_thread() {
+		let shared = SyncShared::new(1);
+		let (borrowed, borrowed_rx) = mpsc::channel();
+		let (release, release_rx) = mpsc::channel::<()>();
+
+		let shared_clone = shared.clone();
+		let thread = std::thread::spawn(move || {
+			let mut value = shared_clone.borrow_mut();
+			borrowed.send(()).unwrap();
+			release_rx.recv().unwrap();
+			*value = 2;
+		});
+
+		borrowed_rx.recv().unwrap();
+		assert!(matches!(shared.try_borrow(), Err(TryBorrowError::WouldBlock)));
+		release.send(()).unwrap();
+		thread.join().unwrap();
+
+		assert_eq!(*shared.try_borrow().unwrap(), 2);
+	}
+
+	#[test]
+	fn upgrades_weak_references_while_alive() {
+		let shared = SyncShared::new(1);
+		let weak = shared.get_weak();
+		assert_eq!(*weak.to_shared().unwrap().borrow(), 1);
+		assert_eq!(*SyncShared::from_weak(&weak.clone()).unwrap().borrow(), 1);
+
+		drop(shared);
+		assert!(weak.to_shared().is_err());
+	}
+
+	#[test]
+	fn recovers_from_poisoning() {
+		let shared = SyncShared::new(vec![ 1 ]);
+
+		let shared_clone = shared.clone();
+		let result = std::thread::spawn(move || {
+			shared_clone.borrow_mut().push(2);
+			let _value = shared_clone.borrow_mut();
+			panic!("The task failed.");
+		}).join();
+
+		assert!(result.is_err());
+		assert!(shared.is_poisoned());
+		assert_eq!(*shared.borrow(), vec![ 1, 2 ]);
+
+		match shared.try_borrow_mut() {
+			Err(TryBorrowError::Poisoned(mut value)) => value.push(3),
+			other => panic!("Expected a poisoned borrow, got {:?}.", other.map(|_| ()))
+		}
+
+		assert!(matches!(shared.try_borrow(), Err(TryBorrowError::Poisoned(value)) if *value == vec![ 1, 2, 3 ]));
+		assert_eq!(shared.replace(vec![]), vec![ 1, 2, 3 ]);
+		assert!(shared.is_poisoned());
+	}
+}
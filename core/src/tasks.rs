@@ -0,0 +1,94 @@
+/**! Lets plugins run work in the background, and on Scout's main thread. */
+
+use std::sync::Arc;
+use std::time::Duration;
+
+/**
+ * Identifies a timer scheduled by `Tasks::every`, so that it can be cancelled.
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(pub usize);
+
+/**
+ * Runs tasks for a plugin. This is implemented by the app, plugins use it through `Tasks`.
+ */
+
+pub trait TaskRunner: Send + Sync {
+
+	/** Runs a task on a background thread. */
+	fn spawn(&self, task: Box<dyn FnOnce() + Send>);
+
+	/** Runs a task on the main thread, once the main loop is idle. */
+	fn run_on_main(&self, task: Box<dyn FnOnce() + Send>);
+
+	/** Calls a task on the main thread at an interval, until it returns false or is cancelled. */
+	fn schedule(&self, interval: Duration, task: Box<dyn FnMut() -> bool + Send>) -> TimerId;
+
+	/** Cancels a scheduled task. */
+	fn cancel(&self, timer: TimerId);
+}
+
+/**
+ * Runs a plugin's work in the background, and passes results back to the main thread.
+ * This is provided by a plugin's bindings, and can be cloned cheaply and used from any thread.
+ * Tasks and timers are stopped when the plugin is unloaded.
+ */
+
+#[derive(Clone)]
+pub struct Tasks {
+	runner: Arc<dyn TaskRunner>
+}
+
+impl Tasks {
+	pub fn new(runner: Arc<dyn TaskRunner>) -> Self {
+		Tasks { runner }
+	}
+
+	/**
+	 * Runs work on a background thread from Scout's thread pool.
+	 */
+
+	pub fn spawn<F: FnOnce() + Send + 'static>(&self, work: F) {
+		self.runner.spawn(Box::new(work));
+	}
+
+	/**
+	 * Runs work on a background thread, then passes its result to `on_done` on the main thread,
+	 * where it is safe to use GTK and `Shared` values.
+	 */
+
+	pub fn spawn_then<T, F, D>(&self, work: F, on_done: D) where
+		T: Send + 'static, F: FnOnce() -> T + Send + 'static, D: FnOnce(T) + Send + 'static {
+		let runner = self.runner.clone();
+		self.runner.spawn(Box::new(move || {
+			let result = work();
+			runner.run_on_main(Box::new(move || on_done(result)));
+		}));
+	}
+
+	/**
+	 * Runs a task on the main thread.
+	 */
+
+	pub fn run_on_main<F: FnOnce() + Send + 'static>(&self, task: F) {
+		self.runner.run_on_main(Box::new(task));
+	}
+
+	/**
+	 * Calls a task on the main thread every interval, until it returns false or is cancelled.
+	 * Long-running work should be spawned from the task, rather than done in it.
+	 */
+
+	pub fn every<F: FnMut() -> bool + Send + 'static>(&self, interval: Duration, task: F) -> TimerId {
+		self.runner.schedule(interval, Box::new(task))
+	}
+
+	/**
+	 * Cancels a task scheduled with `every`.
+	 */
+
+	pub fn cancel(&self, timer: TimerId) {
+		self.runner.cancel(timer);
+	}
+}
@@ -0,0 +1,494 @@
+/*!
+ * Utilities for testing plugins without running Scout.
+ * These are enabled by the `testing` feature, which plugins can enable for their tests only.
+ *
+ * ```toml
+ * [dev-dependencies]
+ * scout-core = { path = "../../core", features = [ "testing" ] }
+ * ```
+ *
+ * A plugin can be tested by calling its register function directly, in a unit test,
+ * or by loading its built library the same way Scout does, in an integration test.
+ *
+ * ```rust,ignore
+ * #[test]
+ * fn finds_firefox() {
+ *   let plugin = scout_core::testing::TestPlugin::from_register(register).unwrap();
+ *   plugin.assert_top("firefox", "Firefox");
+ * }
+ *
+ * #[test]
+ * fn loads() {
+ *   let plugin = scout_core::testing::TestPlugin::load_cdylib("scout_plugin_application").unwrap();
+ *   assert_eq!(plugin.get_identifier(), "application");
+ * }
+ * ```
+ */
+
+#![allow(improper_ctypes_definitions)]
+
+use std::sync::{ Arc, Mutex };
+use std::time::Duration;
+use std::collections::VecDeque;
+use std::path::{ Path, PathBuf };
+
+use crate::{ Shared, Plugin, PluginBindings, PluginDeclaration, PluginLogger, PluginStorage, Notifier, Notification,
+	Updater, ResultUpdate, Tasks, TaskRunner, TimerId, SearchResult, InvocationError };
+
+/** Results scoring under this fraction of the top result's score are dropped, as they are in Scout. */
+pub static CUTOFF: f64 = 0.75;
+
+/**
+ * A call made by a plugin to its bindings.
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindingsCall {
+	Register(String),
+	AddStylesheet(&'static str)
+}
+
+/**
+ * An update to a plugin's results, with the titles of any results pushed.
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateCall {
+	Invalidate,
+	Replace { query: String, titles: Vec<String> }
+}
+
+/**
+ * A logger which keeps the messages logged to it, formatted as `LEVEL target: message`.
+ */
+
+#[derive(Default)]
+pub struct CapturedLog {
+	lines: Mutex<Vec<String>>
+}
+
+impl CapturedLog {
+	pub fn get_lines(&self) -> Vec<String> {
+		self.lines.lock().unwrap().clone()
+	}
+}
+
+impl log::Log for CapturedLog {
+	fn enabled(&self, _: &log::Metadata) -> bool { true }
+
+	fn log(&self, record: &log::Record) {
+		self.lines.lock().unwrap().push(format!("{} {}: {}", record.level(), record.target(), record.args()));
+	}
+
+	fn flush(&self) {}
+}
+
+type Task = Box<dyn FnOnce() + Send>;
+type Timer = (TimerId, Box<dyn FnMut() -> bool + Send>);
+
+/**
+ * A task runner that queues tasks and timers until a test runs them, so tests are deterministic.
+ * Background and main thread tasks are both run on the test's thread, in the order they were queued.
+ */
+
+#[derive(Default)]
+pub struct MockTasks {
+	queue: Mutex<VecDeque<Task>>,
+	timers: Mutex<Vec<Timer>>,
+	next_timer: Mutex<usize>
+}
+
+impl MockTasks {
+
+	/**
+	 * Runs queued tasks, including tasks queued while running them, until the queue is empty.
+	 * Returns how many tasks were run.
+	 */
+
+	pub fn run_tasks(&self) -> usize {
+		let mut count = 0;
+		loop {
+			let task = self.queue.lock().unwrap().pop_front();
+			match task {
+				Some(task) => { task(); count += 1; },
+				None => return count
+			}
+		}
+	}
+
+	/**
+	 * Calls every scheduled timer once, as if its interval had passed, then runs the tasks they queue.
+	 * Timers that return false are removed.
+	 */
+
+	pub fn fire_timers(&self) {
+		let timers = std::mem::take(&mut *self.timers.lock().unwrap());
+		let mut timers: Vec<Timer> = timers.into_iter().filter_map(|(id, mut task)| if task() { Some((id, task)) } else { None }).collect();
+
+		let mut current = self.timers.lock().unwrap();
+		timers.extend(current.drain(..));
+		*current = timers;
+		drop(current);
+
+		self.run_tasks();
+	}
+
+	/**
+	 * Returns how many timers are scheduled.
+	 */
+
+	pub fn get_timer_count(&self) -> usize {
+		self.timers.lock().unwrap().len()
+	}
+}
+
+impl TaskRunner for MockTasks {
+	fn spawn(&self, task: Task) {
+		self.queue.lock().unwrap().push_back(task);
+	}
+
+	fn run_on_main(&self, task: Task) {
+		self.queue.lock().unwrap().push_back(task);
+	}
+
+	fn schedule(&self, _: Duration, task: Box<dyn FnMut() -> bool + Send>) -> TimerId {
+		let mut next = self.next_timer.lock().unwrap();
+		let id = TimerId(*next);
+		*next += 1;
+
+		self.timers.lock().unwrap().push((id, task));
+		id
+	}
+
+	fn cancel(&self, timer: TimerId) {
+		self.timers.lock().unwrap().retain(|(id, _)| *id != timer);
+	}
+}
+
+/**
+ * Bindings that record the calls made to them, instead of registering the plugin with the app.
+ */
+
+#[derive(Default)]
+pub struct MockBindings {
+	identifier: String,
+	plugin: Option<Arc<dyn Plugin>>,
+	storage: Option<PluginStorage>,
+	calls: Arc<Mutex<Vec<BindingsCall>>>,
+	log: Arc<CapturedLog>,
+	notifications: Arc<Mutex<Vec<Notification>>>,
+	updates: Arc<Mutex<Vec<UpdateCall>>>,
+	tasks: Arc<MockTasks>
+}
+
+impl MockBindings {
+	pub fn new() -> Self { MockBindings::default() }
+
+	/**
+	 * Returns the calls made to the bindings, which is updated as calls are made.
+	 */
+
+	pub fn get_calls(&self) -> Arc<Mutex<Vec<BindingsCall>>> {
+		self.calls.clone()
+	}
+
+	/**
+	 * Returns the log that loggers created by these bindings write to.
+	 */
+
+	pub fn get_log(&self) -> Arc<CapturedLog> {
+		self.log.clone()
+	}
+
+	/**
+	 * Returns the notifications sent through notifiers created by these bindings, which is updated as they are sent.
+	 */
+
+	pub fn get_notifications(&self) -> Arc<Mutex<Vec<Notification>>> {
+		self.notifications.clone()
+	}
+
+	/**
+	 * Returns the updates sent through updaters created by these bindings, which is updated as they are sent.
+	 */
+
+	pub fn get_updates(&self) -> Arc<Mutex<Vec<UpdateCall>>> {
+		self.updates.clone()
+	}
+
+	/**
+	 * Returns the runner that holds the tasks and timers started through these bindings.
+	 */
+
+	pub fn get_task_runner(&self) -> Arc<MockTasks> {
+		self.tasks.clone()
+	}
+
+	/**
+	 * Creates storage in a temporary directory for the test process, which is shared by plugins with the same identifier.
+	 */
+
+	fn create_storage(identifier: &str) -> PluginStorage {
+		let dir = std::env::temp_dir().join(format!("scout-test-{}", std::process::id())).join(identifier);
+		PluginStorage::new(dir.join("data"), dir.join("cache"), u64::MAX)
+	}
+}
+
+impl PluginBindings for MockBindings {
+	fn register(&mut self, identifier: &str, plugin: Box<dyn Plugin>) {
+		self.calls.lock().unwrap().push(BindingsCall::Register(identifier.to_owned()));
+		self.identifier = identifier.to_owned();
+		self.plugin = Some(Arc::from(plugin));
+		self.storage = Some(MockBindings::create_storage(identifier));
+	}
+
+	fn add_stylesheet(&mut self, stylesheet: &'static str) {
+		self.calls.lock().unwrap().push(BindingsCall::AddStylesheet(stylesheet));
+	}
+
+	fn get_logger(&self) -> PluginLogger {
+		PluginLogger::new(&self.identifier, self.log.clone())
+	}
+
+	fn get_notifier(&self) -> Notifier {
+		let notifications = self.notifications.clone();
+		Notifier::new(&self.identifier, move |notification| notifications.lock().unwrap().push(notification))
+	}
+
+	fn get_updater(&self) -> Updater {
+		let updates = self.updates.clone();
+		Updater::new(&self.identifier, move |_, update| updates.lock().unwrap().push(match update {
+			ResultUpdate::Invalidate => UpdateCall::Invalidate,
+			ResultUpdate::Replace { query, results } =>
+				UpdateCall::Replace { query, titles: results.iter().map(|res| res.get_title()).collect() }
+		}))
+	}
+
+	fn get_tasks(&self) -> Tasks {
+		Tasks::new(self.tasks.clone())
+	}
+
+	fn get_storage(&self) -> PluginStorage {
+		self.storage.clone().unwrap_or_else(|| MockBindings::create_storage(&self.identifier))
+	}
+
+	fn get_plugin(&self) -> crate::Result<(&str, Arc<dyn Plugin>)> {
+		match &self.plugin {
+			Some(plugin) => Ok((&self.identifier, plugin.clone())),
+			None => Err(InvocationError::RegistrationFailed)
+		}
+	}
+}
+
+
+/**
+ * A registered plugin, which can be queried and asserted on.
+ */
+
+pub struct TestPlugin {
+	identifier: String,
+	plugin: Arc<dyn Plugin>,
+	calls: Vec<BindingsCall>,
+	log: Arc<CapturedLog>,
+	notifications: Arc<Mutex<Vec<Notification>>>,
+	updates: Arc<Mutex<Vec<UpdateCall>>>,
+	tasks: Arc<MockTasks>,
+
+	/** The library the plugin was loaded from, which must outlive the plugin. */
+	_lib: Option<Arc<libloading::Library>>
+}
+
+impl TestPlugin {
+
+	/**
+	 * Registers a plugin by calling its register function with mock bindings.
+	 */
+
+	pub fn from_register(register: unsafe extern "C" fn(Shared<Box<dyn PluginBindings>>)) -> crate::Result<Self> {
+		TestPlugin::register(register, None)
+	}
+
+	/**
+	 * Loads a plugin library and registers it, checking its versions the same way Scout does.
+	 */
+
+	pub fn load<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+		unsafe {
+			let library = Arc::new(libloading::Library::new(path.as_ref())
+				.map_err(|err| InvocationError::Other(format!("{:?}", err)))?);
+
+			let decl = library.get::<*mut PluginDeclaration>(b"PLUGIN_DECLARATION\0")
+				.map_err(|err| InvocationError::Other(format!("{:?}", err)))?.read();
+
+			if decl.rustc_version != crate::RUSTC_VERSION || decl.core_version != crate::CORE_VERSION {
+				return Err(InvocationError::Other("Plugin Version Mismatch.".to_owned())); }
+
+			TestPlugin::register(decl.register, Some(library))
+		}
+	}
+
+	/**
+	 * Loads a plugin library built in the same target directory as the running test, by its crate name.
+	 * The library must have been built first, e.g. with `cargo build`.
+	 */
+
+	pub fn load_cdylib(name: &str) -> crate::Result<Self> {
+		TestPlugin::load(TestPlugin::get_cdylib_path(name)?)
+	}
+
+	/**
+	 * Returns the path of a library in the target directory of the running test, e.g. `target/debug/libname.so`.
+	 */
+
+	pub fn get_cdylib_path(name: &str) -> crate::Result<PathBuf> {
+		let exe = std::env::current_exe()?;
+		let dir = exe.parent().filter(|dir| dir.ends_with("deps")).and_then(Path::parent)
+			.or_else(|| exe.parent()).ok_or(InvocationError::Other("No target directory.".to_owned()))?;
+
+		Ok(dir.join([ std::env::consts::DLL_PREFIX, name, std::env::consts::DLL_SUFFIX ].join("")))
+	}
+
+	fn register(register: unsafe extern "C" fn(Shared<Box<dyn PluginBindings>>),
+		library: Option<Arc<libloading::Library>>) -> crate::Result<Self> {
+		let mock = MockBindings::new();
+		let (calls, log) = (mock.get_calls(), mock.get_log());
+		let (notifications, updates, tasks) = (mock.get_notifications(), mock.get_updates(), mock.get_task_runner());
+		let bindings: Shared<Box<dyn PluginBindings>> = Shared::new(Box::new(mock));
+
+		unsafe { register(bindings.clone()); }
+
+		let bindings = bindings.borrow();
+		let (identifier, plugin) = bindings.get_plugin()?;
+		let calls = calls.lock().unwrap().clone();
+		Ok(TestPlugin { identifier: identifier.to_owned(), plugin, calls, log, notifications, updates, tasks, _lib: library })
+	}
+
+	pub fn get_identifier(&self) -> &str {
+		&self.identifier
+	}
+
+	pub fn get_plugin(&self) -> Arc<dyn Plugin> {
+		self.plugin.clone()
+	}
+
+	/**
+	 * Returns the calls the plugin made to its bindings while it was registered, in order.
+	 */
+
+	pub fn get_calls(&self) -> &[BindingsCall] {
+		&self.calls
+	}
+
+	/**
+	 * Returns the stylesheets the plugin added while it was registered.
+	 */
+
+	pub fn get_stylesheets(&self) -> Vec<&'static str> {
+		self.calls.iter().filter_map(|call| match call { BindingsCall::AddStylesheet(s) => Some(*s), _ => None }).collect()
+	}
+
+	/**
+	 * Returns the lines the plugin has logged through the logger from its bindings.
+	 * A plugin's logger is only installed once per library, so this may not include lines from every test.
+	 */
+
+	pub fn get_log_lines(&self) -> Vec<String> {
+		self.log.get_lines()
+	}
+
+	/**
+	 * Returns the notifications the plugin has sent, in order.
+	 */
+
+	pub fn get_notifications(&self) -> Vec<Notification> {
+		self.notifications.lock().unwrap().clone()
+	}
+
+	/**
+	 * Returns the updates the plugin has sent for its results, in order.
+	 */
+
+	pub fn get_updates(&self) -> Vec<UpdateCall> {
+		self.updates.lock().unwrap().clone()
+	}
+
+	/**
+	 * Runs the tasks the plugin has queued, such as indexing started when it was registered.
+	 * Returns how many tasks were run.
+	 */
+
+	pub fn run_tasks(&self) -> usize {
+		self.tasks.run_tasks()
+	}
+
+	/**
+	 * Calls each of the plugin's timers once, then runs the tasks they queue.
+	 */
+
+	pub fn fire_timers(&self) {
+		self.tasks.fire_timers();
+	}
+
+	/**
+	 * Returns how many timers the plugin has scheduled.
+	 */
+
+	pub fn get_timer_count(&self) -> usize {
+		self.tasks.get_timer_count()
+	}
+
+	/**
+	 * Queries the plugin, returning its results ranked the way Scout ranks them, without history.
+	 * Results with a score of zero, or under the cutoff fraction of the top score, are dropped.
+	 */
+
+	pub fn search(&self, query: &str) -> crate::Result<Vec<Box<dyn SearchResult>>> {
+		let mut results = self.plugin.get_results(query)?;
+		results.retain(|res| res.get_score() > 0);
+		results.sort_by(|a, b| b.get_score().cmp(&a.get_score()));
+
+		let min = results.first().map_or(0, |res| (res.get_score() as f64 * CUTOFF) as usize);
+		results.retain(|res| res.get_score() >= min);
+		Ok(results)
+	}
+
+	/**
+	 * Returns the titles of the ranked results for a query, panicking if the query fails.
+	 */
+
+	pub fn get_titles(&self, query: &str) -> Vec<String> {
+		match self.search(query) {
+			Ok(results) => results.iter().map(|res| res.get_title()).collect(),
+			Err(err) => panic!("Query '{}' failed: {:?}", query, err)
+		}
+	}
+
+	/**
+	 * Asserts that the top result for a query has the title given.
+	 */
+
+	pub fn assert_top(&self, query: &str, title: &str) {
+		let titles = self.get_titles(query);
+		assert!(titles.first().map(|s| s.as_str()) == Some(title),
+			"Expected '{}' to be the top result for '{}', got {:?}.", title, query, titles);
+	}
+
+	/**
+	 * Asserts that the ranked results for a query start with the titles given, in order.
+	 */
+
+	pub fn assert_ranked(&self, query: &str, titles: &[&str]) {
+		let results = self.get_titles(query);
+		assert!(results.len() >= titles.len() && results.iter().zip(titles.iter()).all(|(a, b)| a == b),
+			"Expected the results for '{}' to start with {:?}, got {:?}.", query, titles, results);
+	}
+
+	/**
+	 * Asserts that a query returns no results.
+	 */
+
+	pub fn assert_empty(&self, query: &str) {
+		let titles = self.get_titles(query);
+		assert!(titles.is_empty(), "Expected no results for '{}', got {:?}.", query, titles);
+	}
+}
@@ -0,0 +1,53 @@
+/**! Lets plugins update their results while they are shown, for results that change over time. */
+
+use std::sync::Arc;
+
+use crate::SearchResult;
+
+/**
+ * A change to a plugin's shown results.
+ */
+
+pub enum ResultUpdate {
+
+	/** Queries the plugin again for the current query, and replaces its results. */
+	Invalidate,
+
+	/** Replaces the plugin's results, if the query is still the current query. */
+	Replace { query: String, results: Vec<Box<dyn SearchResult>> }
+}
+
+/**
+ * Sends updates to a plugin's shown results. This is provided by a plugin's bindings,
+ * and can be cloned cheaply and used from any thread, such as a thread watching for changes.
+ * Only the plugin's own results are replaced, other plugins' results are kept.
+ */
+
+#[derive(Clone)]
+pub struct Updater {
+	source: String,
+	send: Arc<dyn Fn(&str, ResultUpdate) + Send + Sync>
+}
+
+impl Updater {
+	pub fn new<F: Fn(&str, ResultUpdate) + Send + Sync + 'static>(source: &str, send: F) -> Self {
+		Updater { source: source.to_owned(), send: Arc::new(send) }
+	}
+
+	/**
+	 * Asks for the plugin's results for the current query to be fetched again.
+	 * This does nothing if the window is hidden.
+	 */
+
+	pub fn invalidate(&self) {
+		(self.send)(&self.source, ResultUpdate::Invalidate);
+	}
+
+	/**
+	 * Replaces the plugin's results for a query. This is ignored if the query has since changed.
+	 */
+
+	pub fn push(&self, query: &str, results: Vec<Box<dyn SearchResult>>) {
+		(self.send)(&self.source, ResultUpdate::Replace { query: query.to_owned(), results });
+	}
+}
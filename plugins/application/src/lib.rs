@@ -1,17 +1,20 @@
 use std::env;
 use std::ffi::OsStr;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use freedesktop_entry_parser::parse_entry;
 
-use scout_core::{ Plugin, SearchResult, PluginBindings, Shared };
+use scout_core::{ Plugin, SearchResult, PluginBindings, Shared, SyncShared, Tasks, Updater };
 
 mod result;
 use result::{ Action, ApplicationResult };
 
+/** How often applications are indexed again, to find newly installed ones. */
+static REINDEX_INTERVAL: Duration = Duration::from_secs(300);
+
 pub struct ApplicationPlugin {
-	bindings: Shared<Box<dyn PluginBindings>>,
-	results: Vec<ApplicationResult>
+	results: SyncShared<Vec<ApplicationResult>>
 }
 
 impl ApplicationPlugin {
@@ -77,20 +80,32 @@ impl ApplicationPlugin {
 		found
 	}
 
-	fn new(bindings: Shared<Box<dyn PluginBindings>>) -> Box<dyn Plugin> {
-		gtk::init().unwrap();
+	fn new() -> Self {
+		ApplicationPlugin {
+			results: SyncShared::new(vec![])
+		}
+	}
 
-		Box::new(ApplicationPlugin {
-			bindings,
-			results: ApplicationPlugin::find_applications()
-		})
+	/**
+	 * Indexes applications on a background thread, then refreshes any results being shown.
+	 */
+
+	fn index(results: &SyncShared<Vec<ApplicationResult>>, tasks: &Tasks, updater: &Updater) {
+		let results = results.clone();
+		let updater = updater.clone();
+		tasks.spawn(move || {
+			let found = ApplicationPlugin::find_applications();
+			scout_core::log::info!("Indexed {} applications.", found.len());
+			results.replace(found);
+			updater.invalidate();
+		});
 	}
 }
 
 impl Plugin for ApplicationPlugin {
 	fn get_results(&self, query: &str) -> scout_core::Result<Vec<Box<dyn SearchResult>>> {
 		let query = query.to_lowercase().replace(' ', "");
-		Ok(self.results.iter()
+		Ok(self.results.borrow().iter()
 			.map(|res| {
 				let mut result = res.clone();
 				result.set_score_from_query(&query);
@@ -105,9 +120,111 @@ impl Plugin for ApplicationPlugin {
 #[allow(improper_ctypes_definitions)]
 extern "C" fn register(bindings_shr: Shared<Box<dyn PluginBindings>>) {
 	let mut bindings = bindings_shr.borrow_mut();
-	let plugin = ApplicationPlugin::new(bindings_shr.clone());
+	let plugin = ApplicationPlugin::new();
+	let results = plugin.results.clone();
 	bindings.add_stylesheet(include_str!("../style/.build.css"));
-	bindings.register("application", plugin);
+	bindings.register("application", Box::new(plugin));
+	bindings.get_logger().install();
+
+	let (tasks, updater) = (bindings.get_tasks(), bindings.get_updater());
+	ApplicationPlugin::index(&results, &tasks, &updater);
+
+	let tasks_clone = tasks.clone();
+	tasks.every(REINDEX_INTERVAL, move || {
+		ApplicationPlugin::index(&results, &tasks_clone, &updater);
+		true
+	});
 }
 
 scout_core::export_plugin!(register);
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Once;
+	use scout_core::testing::{ TestPlugin, UpdateCall };
+
+	static FIXTURE: Once = Once::new();
+
+	/**
+	 * Writes desktop entries to a temporary data directory, and points the XDG variables at it.
+	 * This is shared by every test, as the environment is shared by the test threads.
+	 */
+
+	fn fixture() {
+		FIXTURE.call_once(|| {
+			let dir = std::env::temp_dir().join(format!("scout-application-test-{}", std::process::id()));
+			let applications = dir.join("data").join("applications");
+			std::fs::create_dir_all(&applications).unwrap();
+			std::fs::create_dir_all(dir.join("home")).unwrap();
+
+			let entry = |file: &str, contents: &str| std::fs::write(applications.join(file),
+				format!("[Desktop Entry]\nType=Application\n{}\n", contents)).unwrap();
+
+			entry("terminal.desktop", "Name=Terminal\nExec=terminal");
+			entry("editor.desktop", "Name=Text Editor\nComment=Edit text files\nExec=editor %F");
+			entry("firefox.desktop", "Name=Firefox\nExec=firefox %u\nCategories=Network;WebBrowser;");
+			entry("hidden.desktop", "Name=Hidden Tool\nExec=hidden\nNoDisplay=true");
+
+			std::env::set_var("XDG_DATA_DIRS", dir.join("data"));
+			std::env::set_var("XDG_DATA_HOME", dir.join("home"));
+		});
+	}
+
+	fn plugin() -> TestPlugin {
+		fixture();
+		TestPlugin::from_register(super::register).unwrap()
+	}
+
+	#[test]
+	fn registers() {
+		let plugin = plugin();
+		assert_eq!(plugin.get_identifier(), "application");
+		assert_eq!(plugin.get_stylesheets().len(), 1);
+	}
+
+	#[test]
+	fn indexes_in_the_background() {
+		let plugin = plugin();
+		plugin.assert_empty("terminal");
+
+		assert_eq!(plugin.run_tasks(), 1);
+		plugin.assert_top("terminal", "Terminal");
+		assert_eq!(plugin.get_updates(), vec![ UpdateCall::Invalidate ]);
+	}
+
+	#[test]
+	fn ranks_results() {
+		let plugin = plugin();
+		plugin.run_tasks();
+
+		plugin.assert_top("firef", "Firefox");
+		plugin.assert_ranked("ter", &[ "Terminal", "Text Editor" ]);
+		plugin.assert_empty("zzz");
+	}
+
+	#[test]
+	fn identifies_results_by_name_and_command() {
+		let plugin = plugin();
+		plugin.run_tasks();
+		assert_eq!(plugin.search("firef").unwrap()[0].get_id(), "Firefox\nfirefox %u");
+	}
+
+	#[test]
+	fn skips_hidden_entries() {
+		let plugin = plugin();
+		plugin.run_tasks();
+		assert!(!plugin.get_titles("hiddentool").contains(&"Hidden Tool".to_owned()));
+	}
+
+	#[test]
+	fn reindexes_periodically() {
+		let plugin = plugin();
+		plugin.run_tasks();
+		assert_eq!(plugin.get_timer_count(), 1);
+
+		plugin.fire_timers();
+		assert_eq!(plugin.get_updates(), vec![ UpdateCall::Invalidate, UpdateCall::Invalidate ]);
+		assert_eq!(plugin.get_timer_count(), 1);
+		plugin.assert_top("terminal", "Terminal");
+	}
+}
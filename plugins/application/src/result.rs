@@ -51,9 +51,6 @@ pub struct ApplicationResult {
 	exec: String,
 	actions: Option<Vec<Action>>,
 
-	widget: gtk::Box,
-	top_button: gtk::Button,
-
 	score: usize
 }
 
@@ -96,92 +93,24 @@ impl ApplicationResult {
 
 
 	/**
-	 * Creates a new Program result, with a corresponding result widget.
+	 * Spawns a shell command, discarding its output.
 	 */
 
-	pub fn new(name: &str, description: &str, category: &str,
-		exec: &str, icon: Option<&str>, actions: Option<Vec<Action>>) -> Self {
+	pub fn launch(exec: &str) {
+		scout_core::log::info!("Executing '{}'", &exec);
+		let args = shell_words::split(&exec).unwrap();
+		std::process::Command::new(&args[0]).args(&args[1..])
+			.stdout(std::process::Stdio::null()).stderr(std::process::Stdio::null()).spawn().unwrap();
+	}
 
-		let widget = gtk::Box::new(gtk::Orientation::Vertical, 0);
-		widget.get_style_context().add_class("Application");
-		widget.set_widget_name("SearchResult");
-		let top_button = gtk::Button::new();
 
-		{
-			top_button.get_style_context().add_class("flat");
-			widget.pack_start(&top_button, true, true, 0);
-
-			let exec = ApplicationResult::format_exec(exec);
-			top_button.connect_clicked(move |_| {
-				println!("Executing '{}'", &exec);
-				let args = shell_words::split(&exec).unwrap();
-				std::process::Command::new(&args[0]).args(&args[1..])
-					.stdout(std::process::Stdio::null()).stderr(std::process::Stdio::null()).spawn().unwrap();
-			});
-
-			let widget_top = gtk::Box::new(gtk::Orientation::Horizontal, 4);
-			top_button.add(&widget_top);
-
-			let icon_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
-			icon_box.get_style_context().add_class("IconBox");
-			widget_top.pack_start(&icon_box, false, false, 4);
-
-			let icon = ApplicationResult::get_icon(icon, 32);
-			icon.set_size_request(32, 32);
-			icon_box.pack_start(&icon, false, false, 0);
-
-			let description_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
-			widget_top.pack_start(&description_box, true, true, 0);
-
-			let category_label = gtk::Label::new(Some(&[ "<span size='small' weight='bold'>", category, "</span>" ].join("")));
-			category_label.get_style_context().add_class("Category");
-			category_label.set_ellipsize(pango::EllipsizeMode::End);
-			category_label.set_use_markup(true);
-			category_label.set_xalign(0.0);
-			description_box.pack_start(&category_label, false, false, 1);
-
-			let label = gtk::Label::new(Some(name));
-			label.set_ellipsize(pango::EllipsizeMode::End);
-			label.set_xalign(0.0);
-			description_box.pack_start(&label, false, false, 1);
-
-			if let Some(actions) = actions.as_ref() {
-				let widget_actions = gtk::Box::new(gtk::Orientation::Vertical, 0);
-				widget.pack_start(&widget_actions, true, true, 0);
-
-				for action in actions {
-					let widget_action_button = gtk::Button::new();
-					widget_action_button.get_style_context().add_class("flat");
-					widget_action_button.get_style_context().add_class("ActionButton");
-					widget_actions.pack_start(&widget_action_button, true, true, 0);
-
-					let exec = ApplicationResult::format_exec(&action.exec);
-					widget_action_button.connect_clicked(move |_| {
-						println!("Executing '{}'", &exec);
-						let args = shell_words::split(&exec).unwrap();
-						std::process::Command::new(&args[0]).args(&args[1..])
-							.stdout(std::process::Stdio::null()).stderr(std::process::Stdio::null()).spawn().unwrap();
-					});
-
-					let widget_action = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-					widget_action_button.add(&widget_action);
-
-					let icon_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
-					icon_box.get_style_context().add_class("IconBox");
-					widget_action.pack_start(&icon_box, false, false, 4);
-
-					let icon = gtk::Image::from_icon_name(Some("start-here-symbolic"), gtk::IconSize::Button);
-					icon.set_size_request(16, 16);
-					icon.set_pixel_size(16);
-					icon_box.pack_start(&icon, false, false, 0);
-
-					let action_label = gtk::Label::new(Some(&action.name));
-					action_label.set_ellipsize(pango::EllipsizeMode::End);
-					action_label.set_xalign(0.0);
-					widget_action.pack_start(&action_label, false, false, 4);
-				}
-			}
-		}
+	/**
+	 * Creates a new Program result.
+	 * Its widgets are created on demand, as results are sent between threads.
+	 */
+
+	pub fn new(name: &str, description: &str, category: &str,
+		exec: &str, icon: Option<&str>, actions: Option<Vec<Action>>) -> Self {
 
 		ApplicationResult {
 			name: name.to_owned(),
@@ -189,9 +118,7 @@ impl ApplicationResult {
 			description: description.to_owned(),
 			icon: icon.and_then(|s| Some(s.to_owned())),
 			exec: exec.to_owned(),
-			top_button,
 			actions,
-			widget,
 			score: 0
 		}
 	}
@@ -221,20 +148,107 @@ impl SearchResult for ApplicationResult {
 		self.score
 	}
 
-	fn set_first(&self, first: bool) -> () {
-		self.top_button.set_can_focus(!first);
+	/**
+	 * Identifies the result by its name and command. They're separated by a newline,
+	 * which desktop entries can't contain, and unlike a NUL can be sent over D-Bus.
+	 */
+
+	fn get_id(&self) -> String {
+		[ &self.name, "\n", &self.exec ].join("")
+	}
+
+	fn get_title(&self) -> String {
+		self.name.clone()
+	}
+
+	fn get_subtitle(&self) -> String {
+		self.description.clone()
+	}
+
+	fn get_actions(&self) -> Vec<String> {
+		self.actions.as_ref().map(|actions| actions.iter().map(|action| action.name.clone()).collect()).unwrap_or_else(|| vec![])
+	}
+
+	fn set_first(&self, widget: &gtk::Widget, first: bool) -> () {
+		let widget = widget.downcast_ref::<gtk::Box>().unwrap();
+		widget.get_children()[0].set_can_focus(!first);
 	}
 
 	fn activate(&self) {
-		let exec = ApplicationResult::format_exec(&self.exec);
-		println!("Executing '{}'", &exec);
-		let args = shell_words::split(&exec).unwrap();
-		std::process::Command::new(&args[0]).args(&args[1..])
-			.stdout(std::process::Stdio::null()).stderr(std::process::Stdio::null()).spawn().unwrap();
+		ApplicationResult::launch(&ApplicationResult::format_exec(&self.exec));
 	}
 
 	fn get_result_widget(&self) -> gtk::Widget {
-		self.widget.clone().upcast()
+		let widget = gtk::Box::new(gtk::Orientation::Vertical, 0);
+		widget.get_style_context().add_class("Application");
+		widget.set_widget_name("SearchResult");
+
+		let top_button = gtk::Button::new();
+		top_button.get_style_context().add_class("flat");
+		widget.pack_start(&top_button, true, true, 0);
+
+		let exec = ApplicationResult::format_exec(&self.exec);
+		top_button.connect_clicked(move |_| ApplicationResult::launch(&exec));
+
+		let widget_top = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+		top_button.add(&widget_top);
+
+		let icon_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+		icon_box.get_style_context().add_class("IconBox");
+		widget_top.pack_start(&icon_box, false, false, 4);
+
+		let icon = ApplicationResult::get_icon(self.icon.as_ref().and_then(|s| Some(s.as_str())), 32);
+		icon.set_size_request(32, 32);
+		icon_box.pack_start(&icon, false, false, 0);
+
+		let description_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+		widget_top.pack_start(&description_box, true, true, 0);
+
+		let category_label = gtk::Label::new(Some(&[ "<span size='small' weight='bold'>", &self.category, "</span>" ].join("")));
+		category_label.get_style_context().add_class("Category");
+		category_label.set_ellipsize(pango::EllipsizeMode::End);
+		category_label.set_use_markup(true);
+		category_label.set_xalign(0.0);
+		description_box.pack_start(&category_label, false, false, 1);
+
+		let label = gtk::Label::new(Some(&self.name));
+		label.set_ellipsize(pango::EllipsizeMode::End);
+		label.set_xalign(0.0);
+		description_box.pack_start(&label, false, false, 1);
+
+		if let Some(actions) = self.actions.as_ref() {
+			let widget_actions = gtk::Box::new(gtk::Orientation::Vertical, 0);
+			widget.pack_start(&widget_actions, true, true, 0);
+
+			for action in actions {
+				let widget_action_button = gtk::Button::new();
+				widget_action_button.get_style_context().add_class("flat");
+				widget_action_button.get_style_context().add_class("ActionButton");
+				widget_actions.pack_start(&widget_action_button, true, true, 0);
+
+				let exec = ApplicationResult::format_exec(&action.exec);
+				widget_action_button.connect_clicked(move |_| ApplicationResult::launch(&exec));
+
+				let widget_action = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+				widget_action_button.add(&widget_action);
+
+				let icon_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+				icon_box.get_style_context().add_class("IconBox");
+				widget_action.pack_start(&icon_box, false, false, 4);
+
+				let icon = gtk::Image::from_icon_name(Some("start-here-symbolic"), gtk::IconSize::Button);
+				icon.set_size_request(16, 16);
+				icon.set_pixel_size(16);
+				icon_box.pack_start(&icon, false, false, 0);
+
+				let action_label = gtk::Label::new(Some(&action.name));
+				action_label.set_ellipsize(pango::EllipsizeMode::End);
+				action_label.set_xalign(0.0);
+				widget_action.pack_start(&action_label, false, false, 4);
+			}
+		}
+
+		widget.upcast()
 	}
 
 	fn get_preview_widget(&self) -> gtk::Widget {
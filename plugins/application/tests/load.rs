@@ -0,0 +1,12 @@
+use scout_core::testing::TestPlugin;
+
+/**
+ * Loads the built library the same way Scout does, checking that it exports a compatible plugin.
+ */
+
+#[test]
+fn loads_library() {
+	let plugin = TestPlugin::load_cdylib("scout_plugin_application").unwrap();
+	assert_eq!(plugin.get_identifier(), "application");
+	assert_eq!(plugin.get_stylesheets().len(), 1);
+}
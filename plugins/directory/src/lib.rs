@@ -6,7 +6,6 @@ mod result;
 use result::{ DirectoryResult };
 
 pub struct DirectoryPlugin {
-	bindings: Shared<Box<dyn PluginBindings>>,
 	results: Vec<DirectoryResult>
 }
 
@@ -19,11 +18,8 @@ impl DirectoryPlugin {
 		}
 	}
 
-	fn new(bindings: Shared<Box<dyn PluginBindings>>) -> Box<dyn Plugin> {
-		gtk::init().unwrap();
-
+	fn new() -> Box<dyn Plugin> {
 		let mut plugin = Box::new(DirectoryPlugin {
-			bindings,
 			results: vec![]
 		});
 
@@ -67,9 +63,60 @@ impl Plugin for DirectoryPlugin {
 #[allow(improper_ctypes_definitions)]
 extern "C" fn register(bindings_shr: Shared<Box<dyn PluginBindings>>) {
 	let mut bindings = bindings_shr.borrow_mut();
-	let plugin = DirectoryPlugin::new(bindings_shr.clone());
+	let plugin = DirectoryPlugin::new();
 	bindings.add_stylesheet(include_str!("../style/.build.css"));
 	bindings.register("directory", plugin);
+	bindings.get_logger().install();
 }
 
 core::export_plugin!(register);
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Once;
+	use core::testing::{ TestPlugin, BindingsCall };
+
+	static FIXTURE: Once = Once::new();
+
+	/**
+	 * Points the home and XDG directories at an empty temporary directory, so only the home,
+	 * executable and font directories are found. This is shared by every test, as the environment is shared.
+	 */
+
+	fn fixture() {
+		FIXTURE.call_once(|| {
+			let dir = std::env::temp_dir().join(format!("scout-directory-test-{}", std::process::id()));
+			std::fs::create_dir_all(&dir).unwrap();
+
+			std::env::set_var("HOME", &dir);
+			for var in &[ "XDG_CONFIG_HOME", "XDG_DATA_HOME", "XDG_BIN_HOME" ] { std::env::remove_var(var); }
+		});
+	}
+
+	fn plugin() -> TestPlugin {
+		fixture();
+		TestPlugin::from_register(super::register).unwrap()
+	}
+
+	#[test]
+	fn registers() {
+		let plugin = plugin();
+		assert_eq!(plugin.get_identifier(), "directory");
+		assert!(matches!(plugin.get_calls(), [ BindingsCall::AddStylesheet(_), BindingsCall::Register(_) ]));
+	}
+
+	#[test]
+	fn finds_directories() {
+		let plugin = plugin();
+		plugin.assert_top("home", "Home");
+		plugin.assert_top("fonts", "Fonts");
+		plugin.assert_top("apps", "Applications");
+	}
+
+	#[test]
+	fn ranks_results() {
+		let plugin = plugin();
+		plugin.assert_ranked("home", &[ "Home" ]);
+		plugin.assert_empty("zzz");
+	}
+}
@@ -2,7 +2,7 @@ use gtk::prelude::*;
 
 use core::SearchResult;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FileResult {
 	name: String,
 	icon: String,
@@ -15,9 +15,7 @@ pub struct DirectoryResult {
 	path_str: String,
 	description: String,
 	path: std::path::PathBuf,
-
-	widget: gtk::Box,
-	top_button: gtk::Button,
+	files: Vec<FileResult>,
 
 	score: usize
 }
@@ -92,7 +90,8 @@ impl DirectoryResult {
 
 
 	/**
-	 * Creates a new Directory result, with a corresponding result widget.
+	 * Creates a new Directory result.
+	 * Its widgets are created on demand, as results are sent between threads.
 	 */
 
 	pub fn new(description: Option<&str>, path: &std::path::Path) -> Self {
@@ -103,86 +102,12 @@ impl DirectoryResult {
 		let mut path_str = format!("{}/", path.to_str().unwrap().to_owned());
 		if path_str.starts_with(&home_str) && path_str.len() > home_str.len() + 1 { path_str = path_str[home_str.len() + 1..].to_owned(); }
 
-		let files = DirectoryResult::get_suggested_files(&path);
-
-		let widget = gtk::Box::new(gtk::Orientation::Vertical, 0);
-		widget.get_style_context().add_class("Application");
-		widget.set_widget_name("SearchResult");
-
-		let top_button = gtk::Button::new();
-		top_button.get_style_context().add_class("flat");
-		widget.pack_start(&top_button, true, true, 0);
-		let path_clone = path.to_owned();
-		top_button.connect_clicked(move |_| drop(opener::open(path_clone.to_str().unwrap())));
-
-		{
-
-			let widget_top = gtk::Box::new(gtk::Orientation::Horizontal, 4);
-			top_button.add(&widget_top);
-
-			let icon_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
-			icon_box.get_style_context().add_class("IconBox");
-			widget_top.pack_start(&icon_box, false, false, 4);
-
-			let icon = gtk::Image::from_icon_name(Some(&icon), gtk::IconSize::Dnd);
-			icon.set_size_request(32, 32);
-			icon_box.pack_start(&icon, false, false, 0);
-
-			let description_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
-			widget_top.pack_start(&description_box, true, true, 0);
-
-			let category_label = gtk::Label::new(Some(&[ "<span size='small' weight='bold'>DIRECTORY</span>" ].join("")));
-			category_label.get_style_context().add_class("Category");
-			category_label.set_ellipsize(pango::EllipsizeMode::End);
-			category_label.set_use_markup(true);
-			category_label.set_xalign(0.0);
-			description_box.pack_start(&category_label, false, false, 1);
-
-			let label = gtk::Label::new(Some(&description));
-			label.set_ellipsize(pango::EllipsizeMode::End);
-			label.set_xalign(0.0);
-			description_box.pack_start(&label, false, false, 1);
-
-			if let Ok(files) = files {
-				if files.len() > 0 {
-					let widget_actions = gtk::Box::new(gtk::Orientation::Vertical, 0);
-					widget.pack_start(&widget_actions, true, true, 0);
-
-					for file in files {
-						let widget_action_button = gtk::Button::new();
-						widget_action_button.get_style_context().add_class("flat");
-						widget_action_button.get_style_context().add_class("ActionButton");
-						widget_actions.pack_start(&widget_action_button, true, true, 0);
-
-						let path_clone = file.path.clone();
-						widget_action_button.connect_clicked(move |_| drop(opener::open(path_clone.to_str().unwrap())));
-
-						let widget_action = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-						widget_action_button.add(&widget_action);
-
-						let icon_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
-						icon_box.get_style_context().add_class("IconBox");
-						widget_action.pack_start(&icon_box, false, false, 4);
-
-						let icon = gtk::Image::from_icon_name(Some(&file.icon), gtk::IconSize::Button);
-						icon.set_size_request(16, 16);
-						icon.set_pixel_size(16);
-						icon_box.pack_start(&icon, false, false, 0);
-
-						let action_label = gtk::Label::new(Some(&file.name));
-						action_label.set_ellipsize(pango::EllipsizeMode::End);
-						action_label.set_xalign(0.0);
-						widget_action.pack_start(&action_label, false, false, 4);
-					}
-				}
-			}
-		}
+		let files = DirectoryResult::get_suggested_files(&path).unwrap_or_else(|_| vec![]);
 
 		DirectoryResult {
 			description, icon,
 			path: path.to_owned(),
-			path_str,
-			top_button, widget,
+			path_str, files,
 			score: 0
 		}
 	}
@@ -211,8 +136,25 @@ impl SearchResult for DirectoryResult {
 		self.score
 	}
 
-	fn set_first(&self, first: bool) -> () {
-		self.top_button.set_can_focus(!first);
+	fn get_id(&self) -> String {
+		self.path.to_string_lossy().into_owned()
+	}
+
+	fn get_title(&self) -> String {
+		self.description.clone()
+	}
+
+	fn get_subtitle(&self) -> String {
+		self.path_str.clone()
+	}
+
+	fn get_actions(&self) -> Vec<String> {
+		self.files.iter().map(|file| file.name.clone()).collect()
+	}
+
+	fn set_first(&self, widget: &gtk::Widget, first: bool) -> () {
+		let widget = widget.downcast_ref::<gtk::Box>().unwrap();
+		widget.get_children()[0].set_can_focus(!first);
 	}
 
 	fn activate(&self) {
@@ -220,7 +162,75 @@ impl SearchResult for DirectoryResult {
 	}
 
 	fn get_result_widget(&self) -> gtk::Widget {
-		self.widget.clone().upcast()
+		let widget = gtk::Box::new(gtk::Orientation::Vertical, 0);
+		widget.get_style_context().add_class("Application");
+		widget.set_widget_name("SearchResult");
+
+		let top_button = gtk::Button::new();
+		top_button.get_style_context().add_class("flat");
+		widget.pack_start(&top_button, true, true, 0);
+		let path_clone = self.path.clone();
+		top_button.connect_clicked(move |_| drop(opener::open(path_clone.to_str().unwrap())));
+
+		let widget_top = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+		top_button.add(&widget_top);
+
+		let icon_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+		icon_box.get_style_context().add_class("IconBox");
+		widget_top.pack_start(&icon_box, false, false, 4);
+
+		let icon = gtk::Image::from_icon_name(Some(&self.icon), gtk::IconSize::Dnd);
+		icon.set_size_request(32, 32);
+		icon_box.pack_start(&icon, false, false, 0);
+
+		let description_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+		widget_top.pack_start(&description_box, true, true, 0);
+
+		let category_label = gtk::Label::new(Some(&[ "<span size='small' weight='bold'>DIRECTORY</span>" ].join("")));
+		category_label.get_style_context().add_class("Category");
+		category_label.set_ellipsize(pango::EllipsizeMode::End);
+		category_label.set_use_markup(true);
+		category_label.set_xalign(0.0);
+		description_box.pack_start(&category_label, false, false, 1);
+
+		let label = gtk::Label::new(Some(&self.description));
+		label.set_ellipsize(pango::EllipsizeMode::End);
+		label.set_xalign(0.0);
+		description_box.pack_start(&label, false, false, 1);
+
+		if self.files.len() > 0 {
+			let widget_actions = gtk::Box::new(gtk::Orientation::Vertical, 0);
+			widget.pack_start(&widget_actions, true, true, 0);
+
+			for file in &self.files {
+				let widget_action_button = gtk::Button::new();
+				widget_action_button.get_style_context().add_class("flat");
+				widget_action_button.get_style_context().add_class("ActionButton");
+				widget_actions.pack_start(&widget_action_button, true, true, 0);
+
+				let path_clone = file.path.clone();
+				widget_action_button.connect_clicked(move |_| drop(opener::open(path_clone.to_str().unwrap())));
+
+				let widget_action = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+				widget_action_button.add(&widget_action);
+
+				let icon_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+				icon_box.get_style_context().add_class("IconBox");
+				widget_action.pack_start(&icon_box, false, false, 4);
+
+				let icon = gtk::Image::from_icon_name(Some(&file.icon), gtk::IconSize::Button);
+				icon.set_size_request(16, 16);
+				icon.set_pixel_size(16);
+				icon_box.pack_start(&icon, false, false, 0);
+
+				let action_label = gtk::Label::new(Some(&file.name));
+				action_label.set_ellipsize(pango::EllipsizeMode::End);
+				action_label.set_xalign(0.0);
+				widget_action.pack_start(&action_label, false, false, 4);
+			}
+		}
+
+		widget.upcast()
 	}
 
 	fn get_preview_widget(&self) -> gtk::Widget {